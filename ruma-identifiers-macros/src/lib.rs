@@ -46,6 +46,18 @@ pub fn event_id(input: TokenStream) -> TokenStream {
     output.into()
 }
 
+#[proc_macro]
+pub fn key_version(input: TokenStream) -> TokenStream {
+    let Input { id, .. } = parse_macro_input!(input as Input);
+    assert!(server_key_id::validate_version(&id.value()).is_ok(), "Invalid key_version");
+
+    let output = quote! {
+        #id
+    };
+
+    output.into()
+}
+
 #[proc_macro]
 pub fn room_alias_id(input: TokenStream) -> TokenStream {
     let Input { dollar_crate, id } = parse_macro_input!(input as Input);
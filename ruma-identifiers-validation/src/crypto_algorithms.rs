@@ -1,20 +1,51 @@
 //! Key algorithms used in Matrix spec.
 
-use std::{
-    convert::TryFrom,
+use alloc::string::{String, ToString};
+use core::{
+    convert::Infallible,
     fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
 };
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use strum::{AsRefStr, Display, EnumString};
+/// Implements `PartialEq` between a key algorithm and `str` / `&str` in both directions, comparing
+/// the string with the algorithm's `as_str()`.
+macro_rules! partial_eq_str {
+    ($algorithm:ty) => {
+        partial_eq_str!(@imp, $algorithm, str);
+        partial_eq_str!(@imp, $algorithm, &str);
+    };
+    (@imp, $algorithm:ty, $str:ty) => {
+        impl PartialEq<$str> for $algorithm {
+            fn eq(&self, other: &$str) -> bool {
+                self.as_str() == AsRef::<str>::as_ref(other)
+            }
+        }
+
+        impl PartialEq<$algorithm> for $str {
+            fn eq(&self, other: &$algorithm) -> bool {
+                AsRef::<str>::as_ref(self) == other.as_str()
+            }
+        }
+    };
+}
 
 /// The basic key algorithms in the specification.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, AsRefStr, Display, EnumString)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "snake_case"))]
+///
+/// This type can hold an arbitrary string. To check for algorithms that are not available as a
+/// documented variant here, use its string representation, obtained through `.as_str()`.
+///
+/// Known algorithms are ordered in the order of the variants below. Algorithms that aren't known
+/// to ruma are ordered after all known ones and compared by their names among themselves.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(from = "String", into = "String")
+)]
 #[non_exhaustive]
-#[strum(serialize_all = "snake_case")]
 pub enum DeviceKeyAlgorithm {
     /// The Ed25519 signature algorithm.
     Ed25519,
@@ -24,50 +55,180 @@ pub enum DeviceKeyAlgorithm {
 
     /// The Curve25519 ECDH algorithm, but the key also contains signatures
     SignedCurve25519,
+
+    #[doc(hidden)]
+    _Custom(String),
 }
 
-impl TryFrom<&'_ str> for DeviceKeyAlgorithm {
-    type Error = strum::ParseError;
+impl DeviceKeyAlgorithm {
+    /// Creates a `DeviceKeyAlgorithm` from a string, ignoring ASCII case for the known algorithms.
+    ///
+    /// This is meant for interoperating with non-conformant implementations. Known algorithms
+    /// are always displayed in their canonical lowercase form, regardless of the input casing.
+    /// Unknown algorithms keep the original string.
+    pub fn from_str_ascii_case_insensitive(s: &str) -> Self {
+        [
+            DeviceKeyAlgorithm::Ed25519,
+            DeviceKeyAlgorithm::Curve25519,
+            DeviceKeyAlgorithm::SignedCurve25519,
+        ]
+        .iter()
+        .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(s))
+        .cloned()
+        .unwrap_or_else(|| s.into())
+    }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        value.parse()
+    /// Creates a string slice from this `DeviceKeyAlgorithm`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            DeviceKeyAlgorithm::Ed25519 => "ed25519",
+            DeviceKeyAlgorithm::Curve25519 => "curve25519",
+            DeviceKeyAlgorithm::SignedCurve25519 => "signed_curve25519",
+            DeviceKeyAlgorithm::_Custom(ref algorithm) => algorithm,
+        }
     }
 }
 
-impl TryFrom<String> for DeviceKeyAlgorithm {
-    type Error = strum::ParseError;
+impl AsRef<str> for DeviceKeyAlgorithm {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        value.parse()
+impl Display for DeviceKeyAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for DeviceKeyAlgorithm {
+    fn from(s: &str) -> DeviceKeyAlgorithm {
+        match s {
+            "ed25519" => DeviceKeyAlgorithm::Ed25519,
+            "curve25519" => DeviceKeyAlgorithm::Curve25519,
+            "signed_curve25519" => DeviceKeyAlgorithm::SignedCurve25519,
+            _ => DeviceKeyAlgorithm::_Custom(s.into()),
+        }
+    }
+}
+
+impl From<String> for DeviceKeyAlgorithm {
+    fn from(s: String) -> DeviceKeyAlgorithm {
+        match s.as_str() {
+            "ed25519" => DeviceKeyAlgorithm::Ed25519,
+            "curve25519" => DeviceKeyAlgorithm::Curve25519,
+            "signed_curve25519" => DeviceKeyAlgorithm::SignedCurve25519,
+            _ => DeviceKeyAlgorithm::_Custom(s),
+        }
+    }
+}
+
+impl From<DeviceKeyAlgorithm> for String {
+    fn from(algorithm: DeviceKeyAlgorithm) -> String {
+        algorithm.to_string()
+    }
+}
+
+impl FromStr for DeviceKeyAlgorithm {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
     }
 }
 
 /// The server key algorithms defined in the Matrix spec.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, AsRefStr, Display, EnumString)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "snake_case"))]
+///
+/// This type can hold an arbitrary string. To check for algorithms that are not available as a
+/// documented variant here, use its string representation, obtained through `.as_str()`.
+///
+/// Known algorithms are ordered in the order of the variants below. Algorithms that aren't known
+/// to ruma are ordered after all known ones and compared by their names among themselves.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(from = "String", into = "String")
+)]
 #[non_exhaustive]
-#[strum(serialize_all = "snake_case")]
 pub enum ServerKeyAlgorithm {
     /// The Ed25519 signature algorithm.
     Ed25519,
+
+    #[doc(hidden)]
+    _Custom(String),
+}
+
+impl ServerKeyAlgorithm {
+    /// Creates a `ServerKeyAlgorithm` from a string, ignoring ASCII case for the known algorithms.
+    ///
+    /// This is meant for interoperating with non-conformant implementations. Known algorithms
+    /// are always displayed in their canonical lowercase form, regardless of the input casing.
+    /// Unknown algorithms keep the original string.
+    pub fn from_str_ascii_case_insensitive(s: &str) -> Self {
+        [ServerKeyAlgorithm::Ed25519]
+            .iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(s))
+            .cloned()
+            .unwrap_or_else(|| s.into())
+    }
+
+    /// Creates a string slice from this `ServerKeyAlgorithm`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            ServerKeyAlgorithm::Ed25519 => "ed25519",
+            ServerKeyAlgorithm::_Custom(ref algorithm) => algorithm,
+        }
+    }
+}
+
+impl AsRef<str> for ServerKeyAlgorithm {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for ServerKeyAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
 }
 
-impl TryFrom<&'_ str> for ServerKeyAlgorithm {
-    type Error = strum::ParseError;
+impl From<&str> for ServerKeyAlgorithm {
+    fn from(s: &str) -> ServerKeyAlgorithm {
+        match s {
+            "ed25519" => ServerKeyAlgorithm::Ed25519,
+            _ => ServerKeyAlgorithm::_Custom(s.into()),
+        }
+    }
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        value.parse()
+impl From<String> for ServerKeyAlgorithm {
+    fn from(s: String) -> ServerKeyAlgorithm {
+        match s.as_str() {
+            "ed25519" => ServerKeyAlgorithm::Ed25519,
+            _ => ServerKeyAlgorithm::_Custom(s),
+        }
     }
 }
 
-impl TryFrom<String> for ServerKeyAlgorithm {
-    type Error = strum::ParseError;
+impl From<ServerKeyAlgorithm> for String {
+    fn from(algorithm: ServerKeyAlgorithm) -> String {
+        algorithm.to_string()
+    }
+}
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        value.parse()
+impl FromStr for ServerKeyAlgorithm {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
     }
 }
 
+partial_eq_str!(DeviceKeyAlgorithm);
+partial_eq_str!(ServerKeyAlgorithm);
+
 /// An encryption algorithm to be used to encrypt messages sent to a room.
 ///
 /// This type can hold an arbitrary string. To check for events that are not
@@ -129,6 +290,8 @@ impl From<EventEncryptionAlgorithm> for String {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use ruma_serde::test::serde_json_eq;
     use serde_json::json;
 
@@ -141,11 +304,97 @@ mod tests {
         assert_eq!("signed_curve25519".parse(), Ok(DeviceKeyAlgorithm::SignedCurve25519));
     }
 
+    #[test]
+    fn device_key_algorithm_round_trip() {
+        for algorithm in &["ed25519", "curve25519", "signed_curve25519"] {
+            assert_eq!(algorithm.parse::<DeviceKeyAlgorithm>().unwrap().to_string(), *algorithm);
+        }
+        assert_ne!(DeviceKeyAlgorithm::from("signed_curve25519"), DeviceKeyAlgorithm::Curve25519);
+    }
+
+    #[test]
+    fn compare_key_algorithms_with_str() {
+        assert!("ed25519" == DeviceKeyAlgorithm::Ed25519);
+        assert!(DeviceKeyAlgorithm::Ed25519 == "ed25519");
+        assert!(*"signed_curve25519" == DeviceKeyAlgorithm::SignedCurve25519);
+        let custom = DeviceKeyAlgorithm::from("io.ruma.test");
+        assert!(custom == *"io.ruma.test");
+        assert!("curve25519" != DeviceKeyAlgorithm::SignedCurve25519);
+        assert!(DeviceKeyAlgorithm::Curve25519 != "Curve25519");
+
+        assert!("ed25519" == ServerKeyAlgorithm::Ed25519);
+        assert!(ServerKeyAlgorithm::Ed25519 == *"ed25519");
+        assert!("curve25519" != ServerKeyAlgorithm::Ed25519);
+        let custom = ServerKeyAlgorithm::from("io.ruma.test");
+        assert!(custom != "ed25519");
+    }
+
+    #[test]
+    fn key_algorithm_order() {
+        let algorithms: BTreeSet<DeviceKeyAlgorithm> =
+            ["io.ruma.b", "signed_curve25519", "io.ruma.a", "curve25519", "ed25519"]
+                .iter()
+                .map(|&s| s.into())
+                .collect();
+        let algorithms: Vec<_> = algorithms.iter().map(DeviceKeyAlgorithm::as_str).collect();
+        assert_eq!(
+            algorithms,
+            ["ed25519", "curve25519", "signed_curve25519", "io.ruma.a", "io.ruma.b"]
+        );
+
+        let custom = ServerKeyAlgorithm::from("a");
+        assert!(ServerKeyAlgorithm::Ed25519 < custom);
+    }
+
     #[test]
     fn parse_server_key_algorithm() {
         assert_eq!("ed25519".parse(), Ok(ServerKeyAlgorithm::Ed25519));
     }
 
+    #[test]
+    fn parse_key_algorithm_ascii_case_insensitive() {
+        assert_eq!(
+            DeviceKeyAlgorithm::from_str_ascii_case_insensitive("ED25519"),
+            DeviceKeyAlgorithm::Ed25519
+        );
+        assert_eq!(
+            DeviceKeyAlgorithm::from_str_ascii_case_insensitive("Signed_Curve25519"),
+            DeviceKeyAlgorithm::SignedCurve25519
+        );
+        assert_eq!(
+            ServerKeyAlgorithm::from_str_ascii_case_insensitive("Ed25519"),
+            ServerKeyAlgorithm::Ed25519
+        );
+        assert_eq!(
+            ServerKeyAlgorithm::from_str_ascii_case_insensitive("Ed25519").to_string(),
+            "ed25519"
+        );
+        assert_eq!(
+            ServerKeyAlgorithm::from_str_ascii_case_insensitive("IO.Ruma.Test").as_str(),
+            "IO.Ruma.Test"
+        );
+        assert_eq!("Ed25519".parse::<ServerKeyAlgorithm>().unwrap().as_str(), "Ed25519");
+    }
+
+    #[test]
+    fn custom_key_algorithm_round_trip() {
+        let device_key_algorithm: DeviceKeyAlgorithm = "io.ruma.test".into();
+        assert_eq!(device_key_algorithm.as_str(), "io.ruma.test");
+        assert_eq!(device_key_algorithm.to_string(), "io.ruma.test");
+
+        let server_key_algorithm: ServerKeyAlgorithm = "io.ruma.test".into();
+        assert_eq!(server_key_algorithm.as_str(), "io.ruma.test");
+        assert_eq!(server_key_algorithm.to_string(), "io.ruma.test");
+    }
+
+    #[test]
+    fn key_algorithm_serde() {
+        serde_json_eq(DeviceKeyAlgorithm::SignedCurve25519, json!("signed_curve25519"));
+        serde_json_eq(DeviceKeyAlgorithm::_Custom("io.ruma.test".into()), json!("io.ruma.test"));
+        serde_json_eq(ServerKeyAlgorithm::Ed25519, json!("ed25519"));
+        serde_json_eq(ServerKeyAlgorithm::_Custom("io.ruma.test".into()), json!("io.ruma.test"));
+    }
+
     #[test]
     fn event_encryption_algorithm_serde() {
         serde_json_eq(EventEncryptionAlgorithm::MegolmV1AesSha2, json!("m.megolm.v1.aes-sha2"));
@@ -0,0 +1,12 @@
+use crate::Error;
+
+/// Checks that a device ID doesn't contain any ASCII control characters.
+///
+/// The specification doesn't restrict device IDs at all, so this is an opt-in check.
+pub fn validate_strict(s: &str) -> Result<(), Error> {
+    if s.bytes().any(|byte| byte.is_ascii_control()) {
+        return Err(Error::InvalidDeviceId);
+    }
+
+    Ok(())
+}
@@ -1,13 +1,190 @@
-use std::{num::NonZeroU8, str::FromStr};
+use alloc::vec::Vec;
+use core::{convert::TryInto, num::NonZeroU8};
 
-use crate::{crypto_algorithms::DeviceKeyAlgorithm, Error};
+use crate::Error;
 
 pub fn validate(s: &str) -> Result<NonZeroU8, Error> {
-    let colon_idx = NonZeroU8::new(s.find(':').ok_or(Error::MissingDeviceKeyDelimiter)? as u8)
-        .ok_or(Error::UnknownKeyAlgorithm)?;
+    validate_parts(s).map(|(colon_idx, _)| colon_idx)
+}
+
+/// Like `validate`, but additionally requires the part after the colon to be unpadded base64, as
+/// used for the identifiers of one-time keys.
+///
+/// Only the characters `[A-Za-z0-9_-]` are allowed after the colon.
+pub fn validate_base64_identifier(s: &str) -> Result<NonZeroU8, Error> {
+    let (colon_idx, id_start) = validate_parts(s)?;
 
-    DeviceKeyAlgorithm::from_str(&s[0..colon_idx.get() as usize])
-        .map_err(|_| Error::UnknownKeyAlgorithm)?;
+    if !s[id_start..]
+        .bytes()
+        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+    {
+        return Err(Error::InvalidKeyVersion);
+    }
 
     Ok(colon_idx)
 }
+
+/// Validates each of the given device key IDs.
+///
+/// Unlike calling `validate` in a loop, this doesn't stop at the first invalid ID but returns the
+/// index and error of every one of them.
+pub fn validate_all(ids: &[&str]) -> Result<(), Vec<(usize, Error)>> {
+    crate::validate_all(ids, validate)
+}
+
+/// Splits a device key ID into its key algorithm and device ID at the first colon.
+///
+/// This is a lenient alternative to `validate` that doesn't check either part, and returns `None`
+/// instead of an error if the string doesn't contain a colon.
+pub fn split(s: &str) -> Option<(&str, &str)> {
+    crate::split_key_id(s)
+}
+
+/// Like `validate`, but additionally returns the index at which the part after the colon starts.
+pub fn validate_parts(s: &str) -> Result<(NonZeroU8, usize), Error> {
+    // The colon is ASCII, so slicing right before and after it always lands on a char boundary,
+    // even if the algorithm contains multi-byte characters.
+    let colon_idx = s.find(':').ok_or(Error::MissingDeviceKeyDelimiter { len: s.len() })?;
+    let colon_idx = NonZeroU8::new(colon_idx.try_into().map_err(|_| Error::AlgorithmNameTooLong)?)
+        .ok_or(Error::EmptyKeyAlgorithm)?;
+    let id_start = validate_after_colon(s, colon_idx)?;
+
+    Ok((colon_idx, id_start))
+}
+
+/// Like `validate`, but for an ID that hasn't been checked to be valid UTF-8 yet.
+///
+/// Device IDs are opaque, so the only check that has to look at every byte is the one for valid
+/// UTF-8. On success, the ID is returned as a string slice along with the index of the colon.
+pub fn validate_bytes(bytes: &[u8]) -> Result<(&str, NonZeroU8), Error> {
+    let s = core::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?;
+    validate(s).map(|colon_idx| (s, colon_idx))
+}
+
+/// Like `validate`, but uses the given index of the colon instead of searching for it.
+///
+/// This is meant for parsers that already found the colon. `colon_idx` must be the index of the
+/// first colon in `s`, which is checked with debug assertions. In release builds, an index that
+/// doesn't point at a colon results in `Error::MissingDeviceKeyDelimiter`.
+pub fn validate_at(s: &str, colon_idx: NonZeroU8) -> Result<(), Error> {
+    validate_after_colon(s, colon_idx).map(|_| ())
+}
+
+fn validate_after_colon(s: &str, colon_idx: NonZeroU8) -> Result<usize, Error> {
+    let id_start =
+        crate::key_id_colon_at(s, colon_idx, Error::MissingDeviceKeyDelimiter { len: s.len() })?;
+
+    if s[id_start..].is_empty() {
+        return Err(Error::InvalidKeyVersion);
+    }
+
+    Ok(id_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use super::{
+        split, validate, validate_all, validate_at, validate_base64_identifier, validate_bytes,
+        validate_parts,
+    };
+    use crate::Error;
+
+    #[test]
+    fn parts_of_multibyte_algorithm() {
+        let key_id = "äöü.algorithm:DEVICEID";
+        let (colon_idx, id_start) = validate_parts(key_id).unwrap();
+
+        assert_eq!(&key_id[..colon_idx.get() as usize], "äöü.algorithm");
+        assert_eq!(&key_id[id_start..], "DEVICEID");
+    }
+
+    #[test]
+    fn validate_all_reports_every_invalid_id() {
+        assert_eq!(validate_all(&["ed25519:JLAFKJWSCS", "ed25519:JLAFKJWSCS"]), Ok(()));
+        assert_eq!(
+            validate_all(&["JLAFKJWSCS", "ed25519:JLAFKJWSCS", ":key_1"]),
+            Err(vec![
+                (0, Error::MissingDeviceKeyDelimiter { len: 10 }),
+                (2, Error::EmptyKeyAlgorithm)
+            ])
+        );
+    }
+
+    #[test]
+    fn multibyte_character_before_colon() {
+        let key_id = "ä:DEVICEID";
+        let (colon_idx, id_start) = validate_parts(key_id).unwrap();
+        assert_eq!(colon_idx.get(), 2);
+        assert_eq!(&key_id[id_start..], "DEVICEID");
+
+        // 256 bytes, but only 128 characters
+        let long_algorithm = "ä".repeat(128);
+        assert_eq!(
+            validate_parts(&format!("{}:DEVICEID", long_algorithm)).unwrap_err(),
+            Error::AlgorithmNameTooLong
+        );
+    }
+
+    #[test]
+    fn base64_identifier() {
+        assert_eq!(validate_base64_identifier("signed_curve25519:AAAAHg").unwrap().get(), 17);
+        assert_eq!(validate_base64_identifier("ed25519:Ab-_09").unwrap().get(), 7);
+
+        assert_eq!(
+            validate_base64_identifier("signed_curve25519:AAAAHg==").unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+        assert_eq!(
+            validate_base64_identifier("signed_curve25519:AA+/").unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+        assert_eq!(
+            validate_base64_identifier("signed_curve25519:").unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+    }
+
+    #[test]
+    fn split_device_key_id() {
+        assert_eq!(split("a:b"), Some(("a", "b")));
+        assert_eq!(split(":b"), Some(("", "b")));
+        assert_eq!(split("a:"), Some(("a", "")));
+        assert_eq!(split("ab"), None);
+        assert_eq!(split("ed25519:dev:1"), Some(("ed25519", "dev:1")));
+    }
+
+    #[test]
+    fn validate_at_matches_validate() {
+        for s in &["ed25519:JLAFKJWSCS", "ed25519:", "äöü:DEVICE:ID", "signed_curve25519:AAAAHg"]
+        {
+            let colon_idx = NonZeroU8::new(s.find(':').unwrap() as u8).unwrap();
+            assert_eq!(validate_at(s, colon_idx), validate(s).map(|_| ()), "{}", s);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "colon_idx must point at a colon"]
+    fn validate_at_wrong_index() {
+        let _ = validate_at("ed25519:1", NonZeroU8::new(3).unwrap());
+    }
+
+    #[test]
+    fn validate_bytes_matches_validate() {
+        for s in &["ed25519:JLAFKJWSCS", "ed25519:", "äöü:DEVICE:ID", ":DEVICEID", "DEVICEID"] {
+            assert_eq!(
+                validate_bytes(s.as_bytes()),
+                validate(s).map(|colon_idx| (*s, colon_idx)),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn validate_bytes_invalid_utf8() {
+        assert_eq!(validate_bytes(b"ed25519:\xffDEVICE"), Err(Error::InvalidUtf8));
+    }
+}
@@ -1,51 +1,244 @@
 //! Error conditions.
 
-use std::fmt::{self, Display, Formatter};
+use core::fmt::{self, Display, Formatter};
 
 /// An error encountered when trying to parse an invalid ID string.
+///
+/// New variants may be added in minor releases, so matches on this type need a wildcard arm.
+/// To handle errors without matching on the individual variants, use [`kind`](#method.kind) or
+/// [`message`](#method.message).
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
+    /// The key algorithm name exceeds 255 bytes.
+    AlgorithmNameTooLong,
+    /// The key algorithm part of a key ID is empty.
+    EmptyKeyAlgorithm,
+    /// The media ID part of an `mxc://` URI is empty.
+    EmptyMediaId,
     /// The room version ID is empty.
     EmptyRoomVersionId,
     /// The ID's localpart contains invalid characters.
     ///
-    /// Only relevant for user IDs.
+    /// Only relevant for user IDs and group IDs.
     InvalidCharacters,
-    /// The key version contains outside of [a-zA-Z0-9_].
+    /// The device ID contains ASCII control characters.
+    ///
+    /// Only returned by `DeviceId::try_from_strict`.
+    InvalidDeviceId,
+    /// The key algorithm name contains a colon.
+    ///
+    /// Key IDs are split at their first colon, so such an algorithm can't be part of a key ID.
+    InvalidKeyAlgorithm,
+    /// The part of a key ID after the colon is empty or contains invalid characters.
+    ///
+    /// For server key IDs, only `[a-zA-Z0-9_]` is allowed.
     InvalidKeyVersion,
     /// The server name part of the the ID string is not a valid server name.
-    InvalidServerName,
+    ///
+    /// The [`ServerNameError`](enum.ServerNameError.html) describes what is wrong with it.
+    InvalidServerName(ServerNameError),
+    /// The ID is not valid UTF-8.
+    InvalidUtf8,
     /// The ID exceeds 255 bytes (or 32 codepoints for a room version ID).
     MaximumLengthExceeded,
     /// The ID is missing the colon delimiter between localpart and server name.
     MissingDelimiter,
     /// The ID is missing the colon delimiter between key algorithm and device ID.
-    MissingDeviceKeyDelimiter,
+    MissingDeviceKeyDelimiter {
+        /// The length of the rejected input in bytes, to tell empty inputs apart from ones that
+        /// just lack a colon. The input itself is not included.
+        len: usize,
+    },
+    /// The URI doesn't start with `mxc://`.
+    MissingMxcScheme,
     /// The ID is missing the colon delimiter between key algorithm and version.
-    MissingServerKeyDelimiter,
+    MissingServerKeyDelimiter {
+        /// The length of the rejected input in bytes, to tell empty inputs apart from ones that
+        /// just lack a colon. The input itself is not included.
+        len: usize,
+    },
     /// The ID is missing the correct leading sigil.
     MissingSigil,
     /// The key algorithm is not recognized.
+    ///
+    /// Key IDs with unknown algorithms are accepted by `DeviceKeyId` and `ServerKeyId`, so this is
+    /// only returned by conversions that require a known algorithm, like
+    /// `ServerKeyId::to_device_key_id`.
     UnknownKeyAlgorithm,
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let message = match self {
+impl Error {
+    /// A human-readable description of this error, as used by its `Display` implementation.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Error::AlgorithmNameTooLong => "key algorithm name exceeds 255 bytes",
+            Error::EmptyKeyAlgorithm => "key algorithm is empty",
+            Error::EmptyMediaId => "media ID of mxc URI is empty",
             Error::EmptyRoomVersionId => "room version ID is empty",
             Error::InvalidCharacters => "localpart contains invalid characters",
-            Error::InvalidKeyVersion => "key ID version contains invalid characters",
-            Error::InvalidServerName => "server name is not a valid IP address or domain name",
+            Error::InvalidDeviceId => "device ID contains control characters",
+            Error::InvalidKeyAlgorithm => "key algorithm name contains a colon",
+            Error::InvalidKeyVersion => "key ID version is empty or contains invalid characters",
+            Error::InvalidServerName(reason) => reason.message(),
+            Error::InvalidUtf8 => "ID is not valid UTF-8",
             Error::MaximumLengthExceeded => "ID exceeds 255 bytes",
             Error::MissingDelimiter => "colon is required between localpart and server name",
-            Error::MissingDeviceKeyDelimiter => "colon is required between algorithm and device ID",
-            Error::MissingServerKeyDelimiter => "colon is required between algorithm and version",
+            Error::MissingDeviceKeyDelimiter { .. } => {
+                "colon is required between algorithm and device ID"
+            }
+            Error::MissingMxcScheme => "mxc URI doesn't start with mxc://",
+            Error::MissingServerKeyDelimiter { .. } => {
+                "colon is required between algorithm and version"
+            }
             Error::MissingSigil => "leading sigil is incorrect or missing",
             Error::UnknownKeyAlgorithm => "unknown key algorithm specified",
-        };
+        }
+    }
+
+    /// The category of this error.
+    ///
+    /// Unlike the variants of `Error`, which describe the exact problem, the set of error kinds is
+    /// meant to stay stable, so it can be used for things like metrics.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::AlgorithmNameTooLong
+            | Error::EmptyKeyAlgorithm
+            | Error::InvalidKeyAlgorithm
+            | Error::UnknownKeyAlgorithm => ErrorKind::Algorithm,
+            Error::MissingDelimiter
+            | Error::MissingDeviceKeyDelimiter { .. }
+            | Error::MissingMxcScheme
+            | Error::MissingServerKeyDelimiter { .. }
+            | Error::MissingSigil => ErrorKind::Delimiter,
+            Error::EmptyMediaId
+            | Error::EmptyRoomVersionId
+            | Error::InvalidCharacters
+            | Error::InvalidDeviceId
+            | Error::InvalidUtf8
+            | Error::MaximumLengthExceeded => ErrorKind::Identifier,
+            Error::InvalidKeyVersion => ErrorKind::KeyVersion,
+            Error::InvalidServerName(_) => ErrorKind::ServerName,
+        }
+    }
+}
 
-        write!(f, "{}", message)
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
+
+/// The category of an [`Error`], as returned by [`Error::kind`].
+///
+/// [`Error`]: enum.Error.html
+/// [`Error::kind`]: enum.Error.html#method.kind
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The key algorithm of a key ID is empty, too long or not recognized.
+    Algorithm,
+    /// A sigil, scheme or delimiter that structures the ID is missing.
+    Delimiter,
+    /// The ID, or its localpart, is empty, too long or contains invalid characters.
+    Identifier,
+    /// The version of a key ID is invalid.
+    KeyVersion,
+    /// The server name part of the ID is invalid.
+    ServerName,
+}
+
+/// The reason a server name is invalid, as carried by [`Error::InvalidServerName`].
+///
+/// [`Error::InvalidServerName`]: enum.Error.html#variant.InvalidServerName
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ServerNameError {
+    /// The server name is empty.
+    Empty,
+    /// A DNS label of the host is empty, e.g. because of a leading, trailing or doubled dot.
+    EmptyLabel,
+    /// The host contains characters other than `[a-zA-Z0-9.-]`.
+    InvalidCharacters,
+    /// The host starts with `[` but isn't a valid IPv6 address in brackets.
+    InvalidIpv6Address,
+    /// The host is followed by something other than a colon and a valid port.
+    InvalidPort,
+    /// A DNS label of the host exceeds 63 bytes.
+    LabelTooLong,
+}
+
+impl ServerNameError {
+    /// A human-readable description of this error, as used by its `Display` implementation.
+    pub fn message(&self) -> &'static str {
+        match self {
+            ServerNameError::Empty => "server name is empty",
+            ServerNameError::EmptyLabel => "server name contains an empty DNS label",
+            ServerNameError::InvalidCharacters => {
+                "server name contains characters that aren't allowed in a domain name"
+            }
+            ServerNameError::InvalidIpv6Address => "server name is not a valid IPv6 address",
+            ServerNameError::InvalidPort => "server name is not followed by a valid port",
+            ServerNameError::LabelTooLong => "server name contains a DNS label exceeding 63 bytes",
+        }
+    }
+}
+
+impl Display for ServerNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind, ServerNameError};
+
+    #[test]
+    fn error_kinds_and_messages() {
+        let cases = [
+            (Error::AlgorithmNameTooLong, ErrorKind::Algorithm),
+            (Error::EmptyKeyAlgorithm, ErrorKind::Algorithm),
+            (Error::EmptyMediaId, ErrorKind::Identifier),
+            (Error::EmptyRoomVersionId, ErrorKind::Identifier),
+            (Error::InvalidCharacters, ErrorKind::Identifier),
+            (Error::InvalidDeviceId, ErrorKind::Identifier),
+            (Error::InvalidKeyAlgorithm, ErrorKind::Algorithm),
+            (Error::InvalidKeyVersion, ErrorKind::KeyVersion),
+            (Error::InvalidServerName(ServerNameError::Empty), ErrorKind::ServerName),
+            (Error::InvalidUtf8, ErrorKind::Identifier),
+            (Error::MaximumLengthExceeded, ErrorKind::Identifier),
+            (Error::MissingDelimiter, ErrorKind::Delimiter),
+            (Error::MissingDeviceKeyDelimiter { len: 0 }, ErrorKind::Delimiter),
+            (Error::MissingMxcScheme, ErrorKind::Delimiter),
+            (Error::MissingServerKeyDelimiter { len: 0 }, ErrorKind::Delimiter),
+            (Error::MissingSigil, ErrorKind::Delimiter),
+            (Error::UnknownKeyAlgorithm, ErrorKind::Algorithm),
+        ];
+
+        for (error, kind) in cases.iter() {
+            assert_eq!(error.kind(), *kind, "{:?}", error);
+            assert_eq!(error.to_string(), error.message());
+        }
+    }
+
+    #[test]
+    fn server_name_error_messages() {
+        let reasons = [
+            ServerNameError::Empty,
+            ServerNameError::EmptyLabel,
+            ServerNameError::InvalidCharacters,
+            ServerNameError::InvalidIpv6Address,
+            ServerNameError::InvalidPort,
+            ServerNameError::LabelTooLong,
+        ];
+
+        for reason in reasons.iter() {
+            assert_eq!(Error::InvalidServerName(*reason).to_string(), reason.message());
+            assert_eq!(reason.to_string(), reason.message());
+        }
+    }
+}
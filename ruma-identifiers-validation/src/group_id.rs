@@ -0,0 +1,19 @@
+use core::num::NonZeroU8;
+
+use crate::{parse_id, Error};
+
+pub fn validate(s: &str) -> Result<NonZeroU8, Error> {
+    let colon_idx = parse_id(s, &['+'])?;
+    let localpart = &s[1..colon_idx.get() as usize];
+
+    // See https://matrix.org/docs/spec/appendices#group-identifiers
+    if localpart.is_empty()
+        || !localpart
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'z' | b'-' | b'.' | b'=' | b'_' | b'/'))
+    {
+        return Err(Error::InvalidCharacters);
+    }
+
+    Ok(colon_idx)
+}
@@ -1,7 +1,14 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod crypto_algorithms;
+pub mod device_id;
 pub mod device_key_id;
 pub mod error;
 pub mod event_id;
+pub mod group_id;
+pub mod mxc_uri;
 pub mod room_alias_id;
 pub mod room_id;
 pub mod room_id_or_alias_id;
@@ -10,7 +17,8 @@ pub mod server_key_id;
 pub mod server_name;
 pub mod user_id;
 
-use std::num::NonZeroU8;
+use alloc::vec::Vec;
+use core::num::NonZeroU8;
 
 pub use error::Error;
 
@@ -38,3 +46,49 @@ fn parse_id(id: &str, valid_sigils: &[char]) -> Result<NonZeroU8, Error> {
     server_name::validate(&id[colon_idx + 1..])?;
     Ok(NonZeroU8::new(colon_idx as u8).unwrap())
 }
+
+/// Runs `validate` on every string in `ids` and collects the index and error of each one that
+/// fails.
+fn validate_all<T>(
+    ids: &[&str],
+    validate: impl Fn(&str) -> Result<T, Error>,
+) -> Result<(), Vec<(usize, Error)>> {
+    let errors: Vec<_> = ids
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, id)| validate(id).err().map(|err| (idx, err)))
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Splits a key ID into the parts before and after its first colon, without validating them.
+fn split_key_id(s: &str) -> Option<(&str, &str)> {
+    s.find(':').map(|colon_idx| (&s[..colon_idx], &s[colon_idx + 1..]))
+}
+
+/// Checks that the byte at `colon_idx` of a key ID is its first colon, and returns the index at
+/// which the part after the colon starts.
+///
+/// Whether the colon is the first one is only checked in debug builds. In release builds, an index
+/// that doesn't point at a colon results in `missing_delimiter`.
+fn key_id_colon_at(
+    s: &str,
+    colon_idx: NonZeroU8,
+    missing_delimiter: Error,
+) -> Result<usize, Error> {
+    let colon_idx = colon_idx.get() as usize;
+    debug_assert_eq!(s.as_bytes().get(colon_idx), Some(&b':'), "colon_idx must point at a colon");
+    debug_assert!(!s[..colon_idx].contains(':'), "colon_idx must point at the first colon");
+
+    // Since the byte is an ASCII colon, slicing before and after it lands on char boundaries.
+    if s.as_bytes().get(colon_idx) != Some(&b':') {
+        return Err(missing_delimiter);
+    }
+
+    Ok(colon_idx + 1)
+}
@@ -0,0 +1,42 @@
+use crate::{server_name, Error};
+
+const MXC_SCHEME: &str = "mxc://";
+
+/// Validates an `mxc://` URI and returns the index of the slash between the server name and the
+/// media ID.
+pub fn validate(uri: &str) -> Result<usize, Error> {
+    if !uri.starts_with(MXC_SCHEME) {
+        return Err(Error::MissingMxcScheme);
+    }
+
+    let authority_and_path = &uri[MXC_SCHEME.len()..];
+    let slash_idx = authority_and_path.find('/').ok_or(Error::EmptyMediaId)?;
+
+    server_name::validate(&authority_and_path[..slash_idx])?;
+
+    if authority_and_path[slash_idx + 1..].is_empty() {
+        return Err(Error::EmptyMediaId);
+    }
+
+    Ok(MXC_SCHEME.len() + slash_idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+    use crate::Error;
+
+    #[test]
+    fn valid_mxc_uri() {
+        let uri = "mxc://example.org:8448/abcdef";
+        let slash_idx = validate(uri).unwrap();
+
+        assert_eq!(&uri[..slash_idx], "mxc://example.org:8448");
+        assert_eq!(&uri[slash_idx + 1..], "abcdef");
+    }
+
+    #[test]
+    fn missing_media_id_delimiter() {
+        assert_eq!(validate("mxc://example.org").unwrap_err(), Error::EmptyMediaId);
+    }
+}
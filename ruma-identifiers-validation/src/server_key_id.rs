@@ -1,30 +1,181 @@
-use std::{num::NonZeroU8, str::FromStr};
+use alloc::vec::Vec;
+use core::{convert::TryInto, num::NonZeroU8};
 
-use crate::{crypto_algorithms::ServerKeyAlgorithm, Error};
+use crate::Error;
 
 pub fn validate(s: &str) -> Result<NonZeroU8, Error> {
-    let colon_idx = NonZeroU8::new(s.find(':').ok_or(Error::MissingServerKeyDelimiter)? as u8)
-        .ok_or(Error::UnknownKeyAlgorithm)?;
+    validate_parts(s).map(|(colon_idx, _)| colon_idx)
+}
+
+/// Validates each of the given server key IDs.
+///
+/// Unlike calling `validate` in a loop, this doesn't stop at the first invalid ID but returns the
+/// index and error of every one of them.
+pub fn validate_all(ids: &[&str]) -> Result<(), Vec<(usize, Error)>> {
+    crate::validate_all(ids, validate)
+}
+
+/// Splits a server key ID into its key algorithm and version at the first colon.
+///
+/// This is a lenient alternative to `validate` that doesn't check either part, and returns `None`
+/// instead of an error if the string doesn't contain a colon.
+pub fn split(s: &str) -> Option<(&str, &str)> {
+    crate::split_key_id(s)
+}
+
+/// Like `validate`, but additionally returns the index at which the part after the colon starts.
+pub fn validate_parts(s: &str) -> Result<(NonZeroU8, usize), Error> {
+    // The colon is ASCII, so slicing right before and after it always lands on a char boundary,
+    // even if the algorithm contains multi-byte characters.
+    let colon_idx = s.find(':').ok_or(Error::MissingServerKeyDelimiter { len: s.len() })?;
+    let colon_idx = NonZeroU8::new(colon_idx.try_into().map_err(|_| Error::AlgorithmNameTooLong)?)
+        .ok_or(Error::EmptyKeyAlgorithm)?;
+    let id_start = validate_after_colon(s, colon_idx)?;
+
+    Ok((colon_idx, id_start))
+}
+
+/// Like `validate`, but for an ID that hasn't been checked to be valid UTF-8 yet.
+///
+/// Only the key algorithm is checked to be UTF-8 separately. The version is checked to only
+/// contain the ASCII characters `[a-zA-Z0-9_]`, which also ensures that it is valid UTF-8, so the
+/// version is only scanned once. On success, the ID is returned as a string slice along with the
+/// index of the colon.
+///
+/// Inputs that are invalid in more than one way may result in other errors than converting them to
+/// a string slice first, e.g. a version that isn't valid UTF-8 results in `Error::InvalidKeyVersion`
+/// rather than `Error::InvalidUtf8`.
+pub fn validate_bytes(bytes: &[u8]) -> Result<(&str, NonZeroU8), Error> {
+    let colon_idx = bytes
+        .iter()
+        .position(|&b| b == b':')
+        .ok_or(Error::MissingServerKeyDelimiter { len: bytes.len() })?;
+    core::str::from_utf8(&bytes[..colon_idx]).map_err(|_| Error::InvalidUtf8)?;
+    let colon_idx = NonZeroU8::new(colon_idx.try_into().map_err(|_| Error::AlgorithmNameTooLong)?)
+        .ok_or(Error::EmptyKeyAlgorithm)?;
 
-    validate_server_key_algorithm(&s[..colon_idx.get() as usize])?;
-    validate_version(&s[colon_idx.get() as usize + 1..])?;
+    validate_version_bytes(&bytes[colon_idx.get() as usize + 1..])?;
 
-    Ok(colon_idx)
+    // Safety: The algorithm was checked to be valid UTF-8, and the colon and the version consist
+    // of ASCII characters only.
+    let s = unsafe { core::str::from_utf8_unchecked(bytes) };
+    Ok((s, colon_idx))
 }
 
-fn validate_version(version: &str) -> Result<(), Error> {
-    if version.is_empty() {
-        return Err(Error::EmptyRoomVersionId);
-    } else if !version.chars().all(|c| c.is_alphanumeric() || c == '_') {
-        return Err(Error::InvalidCharacters);
+/// Like `validate`, but uses the given index of the colon instead of searching for it.
+///
+/// This is meant for parsers that already found the colon. `colon_idx` must be the index of the
+/// first colon in `s`, which is checked with debug assertions. In release builds, an index that
+/// doesn't point at a colon results in `Error::MissingServerKeyDelimiter`.
+pub fn validate_at(s: &str, colon_idx: NonZeroU8) -> Result<(), Error> {
+    validate_after_colon(s, colon_idx).map(|_| ())
+}
+
+fn validate_after_colon(s: &str, colon_idx: NonZeroU8) -> Result<usize, Error> {
+    let id_start =
+        crate::key_id_colon_at(s, colon_idx, Error::MissingServerKeyDelimiter { len: s.len() })?;
+
+    validate_version(&s[id_start..])?;
+
+    Ok(id_start)
+}
+
+pub fn validate_version(version: &str) -> Result<(), Error> {
+    validate_version_bytes(version.as_bytes())
+}
+
+fn validate_version_bytes(version: &[u8]) -> Result<(), Error> {
+    if version.is_empty()
+        || !version.iter().all(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+    {
+        return Err(Error::InvalidKeyVersion);
     }
 
     Ok(())
 }
 
-fn validate_server_key_algorithm(algorithm: &str) -> Result<(), Error> {
-    match ServerKeyAlgorithm::from_str(algorithm) {
-        Ok(_) => Ok(()),
-        Err(_) => Err(Error::UnknownKeyAlgorithm),
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use super::{split, validate, validate_all, validate_at, validate_bytes, validate_parts};
+    use crate::Error;
+
+    #[test]
+    fn parts_of_multibyte_algorithm() {
+        let key_id = "äöü.algorithm:key_1";
+        let (colon_idx, id_start) = validate_parts(key_id).unwrap();
+
+        assert_eq!(&key_id[..colon_idx.get() as usize], "äöü.algorithm");
+        assert_eq!(&key_id[id_start..], "key_1");
+    }
+
+    #[test]
+    fn validate_all_reports_every_invalid_id() {
+        assert_eq!(validate_all(&["ed25519:key_1", "ed25519:key_1"]), Ok(()));
+        assert_eq!(
+            validate_all(&["ed25519", "ed25519:key_1", ":key_1"]),
+            Err(vec![
+                (0, Error::MissingServerKeyDelimiter { len: 7 }),
+                (2, Error::EmptyKeyAlgorithm)
+            ])
+        );
+    }
+
+    #[test]
+    fn multibyte_character_before_colon() {
+        let key_id = "ä:key_1";
+        let (colon_idx, id_start) = validate_parts(key_id).unwrap();
+        assert_eq!(colon_idx.get(), 2);
+        assert_eq!(&key_id[id_start..], "key_1");
+
+        // 256 bytes, but only 128 characters
+        let long_algorithm = "ä".repeat(128);
+        assert_eq!(
+            validate_parts(&format!("{}:key_1", long_algorithm)).unwrap_err(),
+            Error::AlgorithmNameTooLong
+        );
+    }
+
+    #[test]
+    fn split_server_key_id() {
+        assert_eq!(split("ed25519:key_1"), Some(("ed25519", "key_1")));
+        assert_eq!(split("ed25519:"), Some(("ed25519", "")));
+        assert_eq!(split("ed25519"), None);
+    }
+
+    #[test]
+    fn validate_at_matches_validate() {
+        for s in &["ed25519:1", "ed25519:", "ed25519:Abc-1", "äöü:key_1", "ed25519:a:b"] {
+            let colon_idx = NonZeroU8::new(s.find(':').unwrap() as u8).unwrap();
+            assert_eq!(validate_at(s, colon_idx), validate(s).map(|_| ()), "{}", s);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "colon_idx must point at a colon"]
+    fn validate_at_wrong_index() {
+        let _ = validate_at("ed25519:1", NonZeroU8::new(3).unwrap());
+    }
+
+    #[test]
+    fn validate_bytes_matches_validate() {
+        for s in &["ed25519:1", "ed25519:", "ed25519:Abc-1", "äöü:key_1", "ed25519:a:b", ":1", "1"]
+        {
+            assert_eq!(
+                validate_bytes(s.as_bytes()),
+                validate(s).map(|colon_idx| (*s, colon_idx)),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn validate_bytes_invalid_utf8() {
+        assert_eq!(validate_bytes(b"ed\xff25519:1"), Err(Error::InvalidUtf8));
+        // The version is only checked against the version grammar, which excludes non-ASCII bytes.
+        assert_eq!(validate_bytes(b"ed25519:\xff"), Err(Error::InvalidKeyVersion));
     }
 }
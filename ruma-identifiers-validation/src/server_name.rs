@@ -1,31 +1,43 @@
-use crate::error::Error;
+use crate::error::{Error, ServerNameError};
 
 pub fn validate(server_name: &str) -> Result<(), Error> {
-    use std::net::Ipv6Addr;
+    validate_reason(server_name).map_err(Error::InvalidServerName)
+}
 
+fn validate_reason(server_name: &str) -> Result<(), ServerNameError> {
     if server_name.is_empty() {
-        return Err(Error::InvalidServerName);
+        return Err(ServerNameError::Empty);
     }
 
     let end_of_host = if server_name.starts_with('[') {
         let end_of_ipv6 = match server_name.find(']') {
             Some(idx) => idx,
-            None => return Err(Error::InvalidServerName),
+            None => return Err(ServerNameError::InvalidIpv6Address),
         };
 
-        if server_name[1..end_of_ipv6].parse::<Ipv6Addr>().is_err() {
-            return Err(Error::InvalidServerName);
+        if !is_ipv6_addr(&server_name[1..end_of_ipv6]) {
+            return Err(ServerNameError::InvalidIpv6Address);
         }
 
         end_of_ipv6 + 1
     } else {
         let end_of_host = server_name.find(':').unwrap_or_else(|| server_name.len());
 
-        if server_name[..end_of_host]
-            .bytes()
-            .any(|byte| !(byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.'))
+        let host = &server_name[..end_of_host];
+        if host.bytes().any(|byte| !(byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.'))
         {
-            return Err(Error::InvalidServerName);
+            return Err(ServerNameError::InvalidCharacters);
+        }
+
+        // Every label of a DNS name must be between 1 and 63 characters long. This also rejects
+        // trailing dots.
+        for label in host.split('.') {
+            if label.is_empty() {
+                return Err(ServerNameError::EmptyLabel);
+            }
+            if label.len() > 63 {
+                return Err(ServerNameError::LabelTooLong);
+            }
         }
 
         end_of_host
@@ -39,8 +51,157 @@ pub fn validate(server_name: &str) -> Result<(), Error> {
             || server_name[end_of_host + 1..].parse::<u16>().is_err()
         )
     {
-        Err(Error::InvalidServerName)
+        Err(ServerNameError::InvalidPort)
     } else {
         Ok(())
     }
 }
+
+#[cfg(feature = "std")]
+fn is_ipv6_addr(s: &str) -> bool {
+    s.parse::<std::net::Ipv6Addr>().is_ok()
+}
+
+#[cfg(not(feature = "std"))]
+fn is_ipv6_addr(s: &str) -> bool {
+    no_std_net::is_ipv6_addr(s)
+}
+
+/// Address parsing for builds without `std::net`. Accepts the same addresses as the parsers in
+/// `std::net`.
+#[cfg(any(not(feature = "std"), test))]
+mod no_std_net {
+    pub fn is_ipv6_addr(s: &str) -> bool {
+        match s.find("::") {
+            // "::" stands for at least one group of zeros
+            Some(idx) => {
+                match (count_ipv6_groups(&s[..idx], false), count_ipv6_groups(&s[idx + 2..], true))
+                {
+                    (Some(head), Some(tail)) => head + tail < 8,
+                    _ => false,
+                }
+            }
+            None => count_ipv6_groups(s, true) == Some(8),
+        }
+    }
+
+    /// Returns the number of 16-bit groups in a colon-separated list of IPv6 groups, or `None` if
+    /// the list is invalid. If `ipv4_allowed` is `true`, the last group may be an IPv4 address.
+    fn count_ipv6_groups(s: &str, ipv4_allowed: bool) -> Option<usize> {
+        if s.is_empty() {
+            return Some(0);
+        }
+
+        let mut count = 0;
+        let mut groups = s.split(':').peekable();
+        while let Some(group) = groups.next() {
+            if ipv4_allowed && groups.peek().is_none() && group.contains('.') {
+                if !is_ipv4_addr(group) {
+                    return None;
+                }
+
+                count += 2;
+            } else if (1..=4).contains(&group.len())
+                && group.bytes().all(|byte| byte.is_ascii_hexdigit())
+            {
+                count += 1;
+            } else {
+                return None;
+            }
+        }
+
+        Some(count)
+    }
+
+    fn is_ipv4_addr(s: &str) -> bool {
+        let mut octets = 0;
+        for octet in s.split('.') {
+            octets += 1;
+
+            let valid = octet.len() <= 3
+                && octet.bytes().all(|byte| byte.is_ascii_digit())
+                && !(octet.len() > 1 && octet.starts_with('0'))
+                && octet.parse::<u8>().is_ok();
+            if !valid {
+                return false;
+            }
+        }
+
+        octets == 4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use super::{no_std_net, validate};
+    use crate::error::{Error, ServerNameError};
+
+    #[test]
+    fn invalid_server_name_reasons() {
+        let long_label = "a".repeat(64);
+        let cases = [
+            ("", ServerNameError::Empty),
+            ("example.org.", ServerNameError::EmptyLabel),
+            ("example..org", ServerNameError::EmptyLabel),
+            ("exam_ple.org", ServerNameError::InvalidCharacters),
+            ("[::1", ServerNameError::InvalidIpv6Address),
+            ("[example.org]", ServerNameError::InvalidIpv6Address),
+            ("example.org:", ServerNameError::InvalidPort),
+            ("example.org:65536", ServerNameError::InvalidPort),
+            ("[::1]x", ServerNameError::InvalidPort),
+            (&long_label, ServerNameError::LabelTooLong),
+        ];
+
+        for (server_name, reason) in cases.iter() {
+            assert_eq!(
+                validate(server_name),
+                Err(Error::InvalidServerName(*reason)),
+                "{}",
+                server_name
+            );
+        }
+    }
+
+    #[test]
+    fn no_std_ipv6_parsing_matches_std() {
+        let addresses = [
+            "::",
+            "::1",
+            "1::",
+            "fe80::1",
+            "1234:5678::abcd",
+            "1:2:3:4:5:6:7:8",
+            "1:2:3:4:5:6::8",
+            "1:2:3:4:5:6:7::",
+            "::ffff:127.0.0.1",
+            "1:2:3:4:5:6:1.2.3.4",
+            "",
+            ":",
+            ":::",
+            "1:::2",
+            "::1:",
+            ":1::",
+            "1::2::3",
+            "1:2:3:4:5:6:7",
+            "1:2:3:4:5:6:7:8:9",
+            "12345::",
+            "test::1",
+            "::1.2.3",
+            "::1.2.3.256",
+            "::1.2.3.04",
+            "1.2.3.4::",
+            "1:2:3:4:5:6:7:1.2.3.4",
+        ];
+
+        for address in &addresses {
+            assert_eq!(
+                no_std_net::is_ipv6_addr(address),
+                address.parse::<Ipv6Addr>().is_ok(),
+                "{}",
+                address
+            );
+        }
+    }
+}
@@ -1,13 +1,17 @@
-use std::num::NonZeroU8;
+use core::num::NonZeroU8;
 
 use crate::{parse_id, Error};
 
+/// Validates a user ID.
+///
+/// Returns the index of the colon and whether the localpart is fully conforming, i.e. `false` for
+/// historical user IDs.
 pub fn validate(s: &str) -> Result<(NonZeroU8, bool), Error> {
     let colon_idx = parse_id(s, &['@'])?;
     let localpart = &s[1..colon_idx.get() as usize];
-    let is_historical = localpart_is_fully_comforming(localpart)?;
+    let is_fully_conforming = localpart_is_fully_comforming(localpart)?;
 
-    Ok((colon_idx, is_historical))
+    Ok((colon_idx, is_fully_conforming))
 }
 
 /// Check whether the given user id localpart is valid and fully conforming
@@ -29,3 +33,29 @@ pub fn localpart_is_fully_comforming(localpart: &str) -> Result<bool, Error> {
         Ok(is_fully_conforming)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{localpart_is_fully_comforming, validate};
+    use crate::Error;
+
+    #[test]
+    fn modern_localpart() {
+        assert_eq!(localpart_is_fully_comforming("carl.test_1=/-"), Ok(true));
+        assert_eq!(validate("@carl:example.com").map(|(_, c)| c), Ok(true));
+    }
+
+    #[test]
+    fn historical_localpart() {
+        assert_eq!(localpart_is_fully_comforming("CARL"), Ok(false));
+        assert_eq!(localpart_is_fully_comforming("a%b[irc]"), Ok(false));
+        assert_eq!(validate("@CARL:example.com").map(|(_, c)| c), Ok(false));
+    }
+
+    #[test]
+    fn invalid_localpart() {
+        assert_eq!(localpart_is_fully_comforming("te st"), Err(Error::InvalidCharacters));
+        assert_eq!(localpart_is_fully_comforming("ä"), Err(Error::InvalidCharacters));
+        assert_eq!(validate("@te\nst:example.com"), Err(Error::InvalidCharacters));
+    }
+}
@@ -0,0 +1,55 @@
+// `cargo bench` works, but if you use `cargo bench -- --save-baseline <name>`
+// or pass any other args to it, it fails with the error
+// `cargo bench unknown option --save-baseline`.
+// To pass args to criterion, use this form
+// `cargo bench --features criterion --bench <name of the bench> -- --save-baseline <name>`.
+
+#![allow(unused_imports, dead_code)]
+
+use std::convert::TryFrom;
+
+#[cfg(feature = "criterion")]
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ruma_identifiers::{DeviceId, DeviceKeyId, ServerKeyId};
+
+/// The number of IDs whose accessors are called in each iteration.
+const ID_COUNT: usize = 1000;
+
+/// Calls `as_str` on every ID in a loop, which is only fast if it is inlined across crates.
+///
+/// Since Rust 1.75, rustc makes small functions like this inlinable across crates on its own, so
+/// the `#[inline]` hints only make a difference with older compilers. To see their effect with a
+/// current compiler, run this on nightly with `RUSTFLAGS="-Zcross-crate-inline-threshold=never"`.
+#[cfg(feature = "criterion")]
+fn as_str_loop(c: &mut Criterion) {
+    let device_key_ids: Vec<_> = (0..ID_COUNT)
+        .map(|i| DeviceKeyId::try_from(format!("ed25519:DEVICE{}", i)).unwrap())
+        .collect();
+    let server_key_ids: Vec<_> =
+        (0..ID_COUNT).map(|i| ServerKeyId::try_from(format!("ed25519:{}", i)).unwrap()).collect();
+    let device_ids: Vec<_> =
+        (0..ID_COUNT).map(|i| Box::<DeviceId>::from(format!("DEVICE{}", i))).collect();
+
+    c.bench_function("`DeviceKeyId::as_str` on 1000 IDs", |b| {
+        b.iter(|| black_box(&device_key_ids).iter().map(|id| id.as_str().len()).sum::<usize>())
+    });
+    c.bench_function("`ServerKeyId::as_str` on 1000 IDs", |b| {
+        b.iter(|| black_box(&server_key_ids).iter().map(|id| id.as_str().len()).sum::<usize>())
+    });
+    c.bench_function("`DeviceId::as_str` on 1000 IDs", |b| {
+        b.iter(|| black_box(&device_ids).iter().map(|id| id.as_str().len()).sum::<usize>())
+    });
+}
+
+#[cfg(feature = "criterion")]
+criterion_group!(benches, as_str_loop);
+
+#[cfg(feature = "criterion")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "criterion"))]
+fn main() {
+    // To run the benchmarks the "criterion" feature must be enabled use:
+    // `cargo bench --features criterion --bench id_accessors`
+    panic!("Enable the criterion feature to run benchmarks");
+}
@@ -0,0 +1,59 @@
+// `cargo bench` works, but if you use `cargo bench -- --save-baseline <name>`
+// or pass any other args to it, it fails with the error
+// `cargo bench unknown option --save-baseline`.
+// To pass args to criterion, use this form
+// `cargo bench --features criterion --bench <name of the bench> -- --save-baseline <name>`.
+
+#![allow(unused_imports, dead_code)]
+
+use std::{convert::TryFrom, sync::Arc};
+
+#[cfg(feature = "criterion")]
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ruma_identifiers::{DeviceId, DeviceKeyId, ServerKeyId};
+
+/// A 32-byte device ID.
+const DEVICE_ID: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ012345";
+
+#[cfg(feature = "criterion")]
+fn clone_device_id(c: &mut Criterion) {
+    let boxed = Box::<DeviceId>::from(DEVICE_ID);
+    let arc = boxed.to_arc();
+
+    c.bench_function("clone 32-byte `Box<DeviceId>`", |b| b.iter(|| black_box(&boxed).clone()));
+    c.bench_function("clone 32-byte `Arc<DeviceId>`", |b| b.iter(|| Arc::clone(black_box(&arc))));
+}
+
+#[cfg(feature = "criterion")]
+fn clone_key_ids(c: &mut Criterion) {
+    let device_key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+    let one_time_key_id = DeviceKeyId::try_from("signed_curve25519:AAAAHg").unwrap();
+    let server_key_id = ServerKeyId::try_from("ed25519:a_Abc1").unwrap();
+    let shared_device_key_id = Arc::new(device_key_id.clone());
+
+    c.bench_function("clone `DeviceKeyId` `ed25519:JLAFKJWSCS`", |b| {
+        b.iter(|| black_box(&device_key_id).clone())
+    });
+    c.bench_function("clone `DeviceKeyId` `signed_curve25519:AAAAHg`", |b| {
+        b.iter(|| black_box(&one_time_key_id).clone())
+    });
+    c.bench_function("clone `ServerKeyId` `ed25519:a_Abc1`", |b| {
+        b.iter(|| black_box(&server_key_id).clone())
+    });
+    c.bench_function("clone `Arc<DeviceKeyId>`", |b| {
+        b.iter(|| Arc::clone(black_box(&shared_device_key_id)))
+    });
+}
+
+#[cfg(feature = "criterion")]
+criterion_group!(benches, clone_device_id, clone_key_ids);
+
+#[cfg(feature = "criterion")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "criterion"))]
+fn main() {
+    // To run the benchmarks the "criterion" feature must be enabled use:
+    // `cargo bench --features criterion --bench id_clone`
+    panic!("Enable the criterion feature to run benchmarks");
+}
@@ -0,0 +1,94 @@
+// `cargo bench` works, but if you use `cargo bench -- --save-baseline <name>`
+// or pass any other args to it, it fails with the error
+// `cargo bench unknown option --save-baseline`.
+// To pass args to criterion, use this form
+// `cargo bench --features criterion --bench <name of the bench> -- --save-baseline <name>`.
+
+#![allow(unused_imports, dead_code)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "criterion")]
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruma_identifiers::DeviceId;
+
+/// Wraps the system allocator to count the number of allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn device_ids_json() -> String {
+    let ids: Vec<_> = (0..10_000).map(|i| format!("DEVICE{:05}", i)).collect();
+    serde_json::to_string(&ids).unwrap()
+}
+
+/// Deserializes the ids the way it was done before borrowing was supported: by first
+/// deserializing each id into an owned string and then copying it.
+fn deserialize_via_cow(json: &str) -> Vec<Box<DeviceId>> {
+    serde_json::from_str::<Vec<Cow<'_, str>>>(json)
+        .unwrap()
+        .into_iter()
+        .map(|s| Box::<DeviceId>::from(&*s))
+        .collect()
+}
+
+fn deserialize_borrowed(json: &str) -> Vec<Box<DeviceId>> {
+    serde_json::from_str(json).unwrap()
+}
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let _ = f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+#[cfg(feature = "criterion")]
+fn deserialize_device_ids(c: &mut Criterion) {
+    let json = device_ids_json();
+    let cow_allocations = count_allocations(|| deserialize_via_cow(&json));
+    let borrowed_allocations = count_allocations(|| deserialize_borrowed(&json));
+
+    // The allocation counts are part of the benchmark names, so they show up in the output.
+    c.bench_function(
+        &format!(
+            "deserialize 10k `Box<DeviceId>` via `Cow<str>` ({} allocations)",
+            cow_allocations
+        ),
+        |b| b.iter(|| deserialize_via_cow(&json)),
+    );
+    c.bench_function(
+        &format!("deserialize 10k `Box<DeviceId>` borrowed ({} allocations)", borrowed_allocations),
+        |b| b.iter(|| deserialize_borrowed(&json)),
+    );
+}
+
+#[cfg(feature = "criterion")]
+criterion_group!(benches, deserialize_device_ids);
+
+#[cfg(feature = "criterion")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "criterion"))]
+fn main() {
+    // To run the benchmarks the "criterion" feature must be enabled use:
+    // `cargo bench --features criterion --bench id_deserialize`
+    panic!("Enable the criterion feature to run benchmarks");
+}
@@ -0,0 +1,56 @@
+// `cargo bench` works, but if you use `cargo bench -- --save-baseline <name>`
+// or pass any other args to it, it fails with the error
+// `cargo bench unknown option --save-baseline`.
+// To pass args to criterion, use this form
+// `cargo bench --features criterion --bench <name of the bench> -- --save-baseline <name>`.
+
+#![allow(unused_imports, dead_code)]
+
+use std::convert::TryFrom;
+
+#[cfg(feature = "criterion")]
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ruma_identifiers::{DeviceId, ServerKeyId};
+
+/// Builds a server key ID with a version of `len` bytes.
+fn server_key_id_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = b"ed25519:".to_vec();
+    bytes.extend(b"a_Abc1".iter().cycle().take(len));
+    bytes
+}
+
+#[cfg(feature = "criterion")]
+fn server_key_id_from_bytes(c: &mut Criterion) {
+    for &len in &[6, 4096] {
+        let bytes = server_key_id_bytes(len);
+
+        c.bench_function(&format!("`ServerKeyId::try_from(&[u8])` {}-byte version", len), |b| {
+            b.iter(|| ServerKeyId::try_from(black_box(&bytes[..])).unwrap())
+        });
+        c.bench_function(&format!("`ServerKeyId::try_from_bytes` {}-byte version", len), |b| {
+            b.iter(|| ServerKeyId::try_from_bytes(black_box(&bytes)).unwrap())
+        });
+    }
+}
+
+#[cfg(feature = "criterion")]
+fn device_id_from_bytes(c: &mut Criterion) {
+    let bytes = vec![b'A'; 4096];
+
+    c.bench_function("`DeviceId::try_from_bytes` 4096 bytes", |b| {
+        b.iter(|| DeviceId::try_from_bytes(black_box(&bytes)).unwrap())
+    });
+}
+
+#[cfg(feature = "criterion")]
+criterion_group!(benches, server_key_id_from_bytes, device_id_from_bytes);
+
+#[cfg(feature = "criterion")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "criterion"))]
+fn main() {
+    // To run the benchmarks the "criterion" feature must be enabled use:
+    // `cargo bench --features criterion --bench id_from_bytes`
+    panic!("Enable the criterion feature to run benchmarks");
+}
@@ -0,0 +1,39 @@
+//! Parses arbitrary bytes as device and server key IDs and checks that valid ones round-trip.
+//!
+//! Run with `cargo fuzz run key_id_round_trip` from the `ruma-identifiers` directory.
+
+#![no_main]
+
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+use ruma_identifiers::{DeviceKeyId, ServerKeyId};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(key_id) = DeviceKeyId::try_from(data) {
+        assert_eq!(key_id.as_str().parse::<DeviceKeyId>().unwrap(), key_id);
+
+        let (algorithm, device_id, colon_idx) = key_id.as_parts_with_colon_idx();
+        assert_eq!(&key_id.as_str()[..colon_idx], algorithm);
+        assert_eq!(key_id.as_algorithm_str(), algorithm);
+        assert_eq!(key_id.device_id().as_str(), device_id);
+
+        let (algorithm, device_id) = key_id.parts();
+        assert_eq!(DeviceKeyId::try_from_parts(algorithm, device_id).unwrap(), key_id);
+    }
+
+    if let Ok(key_id) = ServerKeyId::try_from(data) {
+        assert_eq!(key_id.as_str().parse::<ServerKeyId>().unwrap(), key_id);
+
+        let (algorithm, version, colon_idx) = key_id.as_parts_with_colon_idx();
+        assert_eq!(&key_id.as_str()[..colon_idx], algorithm);
+        assert_eq!(key_id.as_algorithm_str(), algorithm);
+        assert_eq!(key_id.version(), version);
+
+        let (algorithm, version) = key_id.parts();
+        assert_eq!(ServerKeyId::try_from_parts(algorithm, version).unwrap(), key_id);
+
+        // Every server key ID is also a valid device key ID.
+        assert_eq!(DeviceKeyId::from(&key_id), key_id);
+    }
+});
@@ -1,12 +1,14 @@
 //! Matrix device identifiers.
 
 use std::{
+    borrow::{Borrow, Cow},
+    convert::TryFrom,
     fmt::{self, Display},
     mem,
+    sync::Arc,
 };
 
-#[cfg(feature = "rand")]
-use crate::generate_localpart;
+use crate::Error;
 
 /// A Matrix device ID.
 ///
@@ -14,12 +16,19 @@ use crate::generate_localpart;
 /// simply for its semantic value.
 #[repr(transparent)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde1::Serialize), serde(transparent, crate = "serde1"))]
+#[cfg_attr(feature = "serde1", derive(serde1::Serialize), serde(transparent, crate = "serde1"))]
 pub struct DeviceId(str);
 
 /// An owned device identifier.
 pub type DeviceIdBox = Box<DeviceId>;
 
+/// The characters that randomly generated device IDs consist of.
+///
+/// This is also the alphabet of the checksum checked by `DeviceId::verify_checksum`, which is
+/// available without the `rand` feature.
+pub const DEFAULT_CHARSET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
 impl DeviceId {
     #[allow(clippy::transmute_ptr_to_ptr)]
     fn from_borrowed(s: &str) -> &Self {
@@ -34,22 +43,251 @@ impl DeviceId {
         unsafe { mem::transmute(self) }
     }
 
+    fn from_arc(s: Arc<str>) -> Arc<Self> {
+        unsafe { Arc::from_raw(Arc::into_raw(s) as *const Self) }
+    }
+
     /// Generates a random `DeviceId`, suitable for assignment to a new device.
     #[cfg(feature = "rand")]
     #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
     pub fn new() -> Box<Self> {
-        Self::from_owned(generate_localpart(8))
+        Self::new_with_len(8)
+    }
+
+    /// Generates a random `DeviceId` of `len` characters, suitable for assignment to a new device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn new_with_len(len: usize) -> Box<Self> {
+        Self::new_from_rng(&mut rand::thread_rng(), len)
+    }
+
+    /// Generates `n` distinct random `DeviceId`s of `len` characters each.
+    ///
+    /// The device IDs are generated like [`new_with_len`](#method.new_with_len) does it. If a
+    /// device ID was already generated as part of the batch, a new one is generated in its place,
+    /// so the returned device IDs are guaranteed to be distinct. At most `16 * n` device IDs are
+    /// generated in total, which is only ever exceeded if `n` is close to the number of possible
+    /// device IDs of length `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero, if `n` is greater than the number of possible device IDs of
+    /// length `len`, or if `n` distinct device IDs couldn't be generated within `16 * n` attempts.
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn generate_batch(n: usize, len: usize) -> Vec<Box<Self>> {
+        use std::collections::HashSet;
+
+        assert!(len > 0, "device ID length must be greater than zero");
+        if let Some(possible_ids) = (DEFAULT_CHARSET.len() as u128).checked_pow(len as u32) {
+            assert!(
+                n as u128 <= possible_ids,
+                "can't generate {} distinct device IDs of length {}",
+                n,
+                len
+            );
+        }
+
+        let mut seen = HashSet::with_capacity(n);
+        let mut device_ids = Vec::with_capacity(n);
+        let mut attempts = 0;
+
+        while device_ids.len() < n {
+            assert!(attempts < n.saturating_mul(16), "too many device ID collisions");
+            attempts += 1;
+
+            let device_id = Self::new_with_len(len);
+            if seen.insert(device_id.clone()) {
+                device_ids.push(device_id);
+            }
+        }
+
+        device_ids
+    }
+
+    /// Generates a random `DeviceId` of `len` characters from
+    /// [`DEFAULT_CHARSET`](constant.DEFAULT_CHARSET.html) using the given random number generator.
+    ///
+    /// This is useful for getting reproducible device IDs, e.g. in tests, by passing a seeded
+    /// generator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn new_from_rng<R: rand::Rng + ?Sized>(rng: &mut R, len: usize) -> Box<Self> {
+        assert!(len > 0, "device ID length must be greater than zero");
+        Self::from_owned(random_chars(rng, DEFAULT_CHARSET, len).into())
+    }
+
+    /// Generates a random `DeviceId` of `len` characters drawn from `charset`.
+    ///
+    /// Every character is picked independently and uniformly from the bytes in `charset`, so a
+    /// byte that appears twice in `charset` is twice as likely to be picked. Use
+    /// [`DEFAULT_CHARSET`](constant.DEFAULT_CHARSET.html) for the characters `new` draws from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero, or if `charset` is empty or contains non-ASCII bytes.
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn new_from_charset(charset: &[u8], len: usize) -> Box<Self> {
+        assert!(len > 0, "device ID length must be greater than zero");
+        assert!(!charset.is_empty(), "charset must not be empty");
+        assert!(charset.is_ascii(), "charset must only contain ASCII characters");
+
+        Self::from_owned(random_chars(&mut rand::thread_rng(), charset, len).into())
+    }
+
+    /// Generates a random `DeviceId` with a checksum, to make typos detectable.
+    ///
+    /// The device ID consists of 8 random characters from [`DEFAULT_CHARSET`], followed by 2
+    /// checksum characters. See [`verify_checksum`](#method.verify_checksum) for how the checksum
+    /// is computed.
+    ///
+    /// [`DEFAULT_CHARSET`]: constant.DEFAULT_CHARSET.html
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn new_with_checksum() -> Box<Self> {
+        let mut device_id =
+            random_chars(&mut rand::thread_rng(), DEFAULT_CHARSET, CHECKSUMMED_RANDOM_LEN);
+        let checksum = checksum(device_id.as_bytes()).expect("random part is alphanumeric");
+        device_id.extend(checksum.iter().map(|&idx| DEFAULT_CHARSET[idx as usize] as char));
+
+        Self::from_owned(device_id.into())
+    }
+
+    /// Checks whether this `DeviceId` has the format generated by
+    /// [`new_with_checksum`](#method.new_with_checksum) and its checksum is correct.
+    ///
+    /// Such a device ID consists of 10 characters from `[A-Za-z0-9]`. Each character `c` has a
+    /// value `v(c)`: `A`-`Z` are 0-25, `a`-`z` are 26-51 and `0`-`9` are 52-61, i.e. the index of
+    /// `c` in [`DEFAULT_CHARSET`]. For the first 8 characters `c1`, …, `c8`:
+    ///
+    /// * the 9th character is the one with the value `(v(c1) + … + v(c8)) mod 62`
+    /// * the 10th character is the one with the value `(1 * v(c1) + … + 8 * v(c8)) mod 62`
+    ///
+    /// Changing any single character makes the check fail, as does swapping two distinct adjacent
+    /// characters of the random part.
+    ///
+    /// [`DEFAULT_CHARSET`]: constant.DEFAULT_CHARSET.html
+    pub fn verify_checksum(&self) -> bool {
+        let bytes = self.as_bytes();
+        if bytes.len() != CHECKSUMMED_RANDOM_LEN + 2 {
+            return false;
+        }
+
+        let (random_part, checksum_part) = bytes.split_at(CHECKSUMMED_RANDOM_LEN);
+        match (
+            checksum(random_part),
+            charset_index(checksum_part[0]),
+            charset_index(checksum_part[1]),
+        ) {
+            (Some(checksum), Some(first), Some(second)) => checksum == [first, second],
+            _ => false,
+        }
+    }
+
+    /// Creates a `DeviceId` from a string, rejecting ASCII control characters such as newlines or
+    /// NUL bytes.
+    ///
+    /// Device IDs are opaque according to the specification, so the `From` implementations accept
+    /// any string. This is an opt-in check for contexts where control characters would cause
+    /// problems, like logging.
+    pub fn try_from_strict(s: &str) -> Result<Box<Self>, Error> {
+        ruma_identifiers_validation::device_id::validate_strict(s)?;
+        Ok(Self::from_owned(s.into()))
+    }
+
+    /// Creates a `DeviceId` from bytes, which only have to be valid UTF-8.
+    ///
+    /// Device IDs are opaque, so this checks the bytes in a single pass, like
+    /// `<&DeviceId>::try_from(&[u8])`.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<&Self, Error> {
+        crate::str_from_utf8(bytes).map(Self::from_borrowed)
     }
 
     /// Creates a string slice from this `DeviceId`.
+    #[inline]
+    #[must_use]
     pub fn as_str(&self) -> &str {
         &self.0
     }
 
     /// Creates a byte slice from this `DeviceId`.
+    #[inline]
+    #[must_use]
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_bytes()
     }
+
+    /// Returns this `DeviceId` for logging, truncated to `max_chars` characters followed by `…`
+    /// if it is longer.
+    ///
+    /// This never splits a multi-byte character.
+    pub fn truncated(&self, max_chars: usize) -> Cow<'_, str> {
+        crate::truncate_id(self.as_str(), max_chars)
+    }
+
+    /// Creates a reference-counted copy of this `DeviceId`.
+    ///
+    /// Cloning an `Arc<DeviceId>` only increments a reference count instead of copying the
+    /// string, which makes it the better choice for device IDs that are shared a lot.
+    pub fn to_arc(&self) -> Arc<Self> {
+        Self::from_arc(self.as_str().into())
+    }
+
+    /// Returns the length of this `DeviceId` in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether this `DeviceId` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Picks `len` characters independently and uniformly from `charset`, which must be non-empty and
+/// only contain ASCII characters.
+#[cfg(feature = "rand")]
+fn random_chars<R: rand::Rng + ?Sized>(rng: &mut R, charset: &[u8], len: usize) -> String {
+    use rand::seq::SliceRandom as _;
+
+    (0..len).map(|_| *charset.choose(&mut *rng).unwrap() as char).collect()
+}
+
+/// The number of random characters in a device ID generated by `DeviceId::new_with_checksum`.
+const CHECKSUMMED_RANDOM_LEN: usize = 8;
+
+/// The value of a byte for the device ID checksum, i.e. its index in `DEFAULT_CHARSET`.
+fn charset_index(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        _ => None,
+    }
+}
+
+/// Computes the values of the two checksum characters of a device ID, as documented in
+/// `DeviceId::verify_checksum`.
+fn checksum(random_part: &[u8]) -> Option<[u8; 2]> {
+    let mut sum = 0u32;
+    let mut weighted_sum = 0u32;
+
+    for (i, &byte) in random_part.iter().enumerate() {
+        let value = u32::from(charset_index(byte)?);
+        sum += value;
+        weighted_sum += (i as u32 + 1) * value;
+    }
+
+    Some([(sum % 62) as u8, (weighted_sum % 62) as u8])
 }
 
 impl Clone for Box<DeviceId> {
@@ -58,6 +296,15 @@ impl Clone for Box<DeviceId> {
     }
 }
 
+/// Generates a random `DeviceId`, like [`DeviceId::new`](struct.DeviceId.html#method.new).
+#[cfg(feature = "rand")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+impl Default for Box<DeviceId> {
+    fn default() -> Self {
+        DeviceId::new()
+    }
+}
+
 impl ToOwned for DeviceId {
     type Owned = Box<DeviceId>;
 
@@ -72,18 +319,52 @@ impl From<&DeviceId> for Box<DeviceId> {
     }
 }
 
+impl From<&DeviceId> for Arc<DeviceId> {
+    fn from(id: &DeviceId) -> Self {
+        id.to_arc()
+    }
+}
+
 impl AsRef<str> for DeviceId {
+    #[inline]
     fn as_ref(&self) -> &str {
         self.as_str()
     }
 }
 
 impl AsRef<str> for Box<DeviceId> {
+    #[inline]
     fn as_ref(&self) -> &str {
         self.as_str()
     }
 }
 
+impl AsRef<[u8]> for DeviceId {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for Box<DeviceId> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<str> for DeviceId {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Box<DeviceId> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
 impl<'a> From<&'a str> for &'a DeviceId {
     fn from(s: &'a str) -> Self {
         DeviceId::from_borrowed(s)
@@ -102,25 +383,55 @@ impl From<String> for Box<DeviceId> {
     }
 }
 
+impl<'a> TryFrom<&'a [u8]> for &'a DeviceId {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        crate::str_from_utf8(bytes).map(DeviceId::from_borrowed)
+    }
+}
+
+impl TryFrom<&[u8]> for Box<DeviceId> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        crate::str_from_utf8(bytes).map(Into::into)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Box<DeviceId> {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        String::from_utf8(bytes).map(Into::into).map_err(|_| Error::InvalidUtf8)
+    }
+}
+
 impl From<Box<DeviceId>> for String {
     fn from(id: Box<DeviceId>) -> Self {
         id.into_owned().into()
     }
 }
 
+impl From<&DeviceId> for Box<str> {
+    fn from(id: &DeviceId) -> Self {
+        id.as_str().into()
+    }
+}
+
 impl Display for DeviceId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
-#[cfg(feature = "serde")]
+#[cfg(feature = "serde1")]
 impl<'de> serde1::Deserialize<'de> for Box<DeviceId> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde1::Deserializer<'de>,
     {
-        crate::deserialize_id(deserializer, "An IP address or hostname")
+        crate::deserialize_id(deserializer, "a Matrix device ID")
     }
 }
 
@@ -135,12 +446,285 @@ pub fn generate() -> Box<DeviceId> {
     DeviceId::new()
 }
 
-#[cfg(all(test, feature = "rand"))]
+#[cfg(test)]
 mod tests {
+    use std::{borrow::Borrow, collections::BTreeMap, convert::TryFrom, ops::Bound, sync::Arc};
+
+    #[cfg(feature = "serde")]
+    use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
     use super::DeviceId;
+    use crate::Error;
 
+    #[cfg(feature = "rand")]
     #[test]
     fn generate_device_id() {
         assert_eq!(DeviceId::new().as_str().len(), 8);
     }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn default_device_id() {
+        assert_eq!(Box::<DeviceId>::default().as_str().len(), 8);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_device_id_with_len() {
+        for &len in &[1, 10, 32] {
+            assert_eq!(DeviceId::new_with_len(len).as_str().len(), len);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_device_id_from_charset() {
+        let charset = b"AB01";
+        let device_id = DeviceId::new_from_charset(charset, 100);
+        assert_eq!(device_id.as_str().len(), 100);
+        assert!(device_id.as_bytes().iter().all(|b| charset.contains(b)));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_device_id_from_seeded_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let device_id = DeviceId::new_from_rng(&mut StdRng::seed_from_u64(42), 10);
+        assert_eq!(device_id.as_str().len(), 10);
+        assert_eq!(device_id, DeviceId::new_from_rng(&mut StdRng::seed_from_u64(42), 10));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn default_charset() {
+        let device_id = DeviceId::new();
+        assert!(device_id.as_bytes().iter().all(|b| super::DEFAULT_CHARSET.contains(b)));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_device_id_batch() {
+        let device_ids = DeviceId::generate_batch(1000, 8);
+        assert_eq!(device_ids.len(), 1000);
+        assert!(device_ids.iter().all(|device_id| device_id.len() == 8));
+
+        let distinct: std::collections::BTreeSet<_> = device_ids.iter().collect();
+        assert_eq!(distinct.len(), 1000);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_small_device_id_batch() {
+        let device_ids = DeviceId::generate_batch(10, 1);
+        let distinct: std::collections::BTreeSet<_> = device_ids.iter().collect();
+        assert_eq!(distinct.len(), 10);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic]
+    fn generate_too_large_device_id_batch() {
+        DeviceId::generate_batch(63, 1);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generate_device_id_with_checksum() {
+        let device_id = DeviceId::new_with_checksum();
+        assert_eq!(device_id.len(), 10);
+        assert!(device_id.as_bytes().iter().all(|b| super::DEFAULT_CHARSET.contains(b)));
+        assert!(device_id.verify_checksum());
+    }
+
+    #[test]
+    fn checksum_values_are_default_charset_indices() {
+        for (idx, &byte) in super::DEFAULT_CHARSET.iter().enumerate() {
+            assert_eq!(super::charset_index(byte), Some(idx as u8));
+        }
+    }
+
+    #[test]
+    fn verify_device_id_checksum() {
+        assert!(<&DeviceId>::from("ABCDEFGHcs").verify_checksum());
+        assert!(<&DeviceId>::from("00000000sM").verify_checksum());
+
+        assert!(!<&DeviceId>::from("ABCDEFGH").verify_checksum());
+        assert!(!<&DeviceId>::from("ABCDEFGHcsX").verify_checksum());
+        assert!(!<&DeviceId>::from("ABCDEFGHc-").verify_checksum());
+        assert!(!<&DeviceId>::from("BACDEFGHcs").verify_checksum());
+    }
+
+    #[test]
+    fn device_id_checksum_detects_single_character_changes() {
+        let charset = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+        let device_id = "ABCDEFGHcs";
+
+        for i in 0..device_id.len() {
+            for &replacement in charset {
+                if replacement == device_id.as_bytes()[i] {
+                    continue;
+                }
+
+                let mut mutated = device_id.as_bytes().to_vec();
+                mutated[i] = replacement;
+                let mutated = String::from_utf8(mutated).unwrap();
+                assert!(!<&DeviceId>::from(mutated.as_str()).verify_checksum(), "{}", mutated);
+            }
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic]
+    fn generate_device_id_from_non_ascii_charset() {
+        DeviceId::new_from_charset("é".as_bytes(), 8);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic = "device ID length must be greater than zero"]
+    fn generate_empty_device_id_from_charset() {
+        DeviceId::new_from_charset(b"AB01", 0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic]
+    fn generate_empty_device_id() {
+        DeviceId::new_with_len(0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic = "device ID length must be greater than zero"]
+    fn generate_empty_device_id_from_rng() {
+        DeviceId::new_from_rng(&mut rand::thread_rng(), 0);
+    }
+
+    #[test]
+    fn device_id_from_bytes() {
+        assert_eq!(<&DeviceId>::try_from(&b"ABCDEFGH"[..]).unwrap(), "ABCDEFGH");
+        assert_eq!(Box::<DeviceId>::try_from(&b"ABCDEFGH"[..]).unwrap(), "ABCDEFGH");
+        assert_eq!(
+            Box::<DeviceId>::try_from(&b"ABC\xffDEFGH"[..]).unwrap_err(),
+            Error::InvalidUtf8
+        );
+    }
+
+    #[test]
+    fn device_id_try_from_bytes() {
+        assert_eq!(DeviceId::try_from_bytes(b"ABCDEFGH").unwrap(), "ABCDEFGH");
+        assert_eq!(DeviceId::try_from_bytes(b"ABC\xffDEFGH").unwrap_err(), Error::InvalidUtf8);
+        assert_eq!(Box::<DeviceId>::try_from(b"ABCDEFGH".to_vec()).unwrap(), "ABCDEFGH");
+        assert_eq!(
+            Box::<DeviceId>::try_from(b"ABC\xffDEFGH".to_vec()).unwrap_err(),
+            Error::InvalidUtf8
+        );
+    }
+
+    #[test]
+    fn strict_device_id() {
+        assert_eq!(DeviceId::try_from_strict("ABCDEFGH").unwrap(), "ABCDEFGH");
+        assert_eq!(DeviceId::try_from_strict("ABCD\nEFGH").unwrap_err(), Error::InvalidDeviceId);
+        assert_eq!(DeviceId::try_from_strict("ABCD\0EFGH").unwrap_err(), Error::InvalidDeviceId);
+        assert_eq!(Box::<DeviceId>::from("ABCD\nEFGH"), "ABCD\nEFGH");
+    }
+
+    #[test]
+    fn device_id_to_arc() {
+        let device_id: Box<DeviceId> = "MYDEVICEID".into();
+        let arc = device_id.to_arc();
+        let clone = Arc::clone(&arc);
+
+        assert_eq!(*arc, *device_id);
+        assert_eq!(clone.as_str(), "MYDEVICEID");
+        assert!(Arc::ptr_eq(&arc, &clone));
+        assert_eq!(Arc::strong_count(&arc), 2);
+    }
+
+    #[test]
+    fn arc_device_id_from_box() {
+        let arc = Arc::<DeviceId>::from(Box::<DeviceId>::from("MYDEVICEID"));
+        assert_eq!(arc.as_str(), "MYDEVICEID");
+        assert_eq!(arc.len(), 10);
+    }
+
+    #[test]
+    fn device_id_as_bytes_ref() {
+        fn digest_input(bytes: impl AsRef<[u8]>) -> Vec<u8> {
+            bytes.as_ref().to_vec()
+        }
+
+        let device_id: Box<DeviceId> = "MYDEVICEID".into();
+        assert_eq!(digest_input(&*device_id), b"MYDEVICEID");
+        assert_eq!(digest_input(&device_id), b"MYDEVICEID");
+    }
+
+    #[test]
+    fn device_id_len() {
+        assert_eq!(Box::<DeviceId>::from("ABCDEFGH").len(), 8);
+        assert!(!Box::<DeviceId>::from("ABCDEFGH").is_empty());
+        assert!(<&DeviceId>::from("").is_empty());
+    }
+
+    #[test]
+    fn borrow_device_id_as_str() {
+        let mut map = BTreeMap::new();
+        map.insert(Box::<DeviceId>::from("ABCDEFGH"), 1);
+        assert_eq!(map.get("ABCDEFGH"), Some(&1));
+        assert_eq!(map.get("HGFEDCBA"), None);
+    }
+
+    #[test]
+    fn truncated_device_id() {
+        let device_id = <&DeviceId>::from("ABCDEFGH");
+        assert_eq!(device_id.truncated(8), "ABCDEFGH");
+
+        let device_id = <&DeviceId>::from("ÄÖÜABCDE");
+        assert_eq!(device_id.truncated(3), "ÄÖÜ…");
+        assert_eq!(device_id.truncated(1), "Ä…");
+    }
+
+    #[test]
+    fn device_id_into_boxed_str() {
+        let device_id = <&DeviceId>::from("ABCDEFGH");
+        let boxed = Box::<str>::from(device_id);
+        assert_eq!(&*boxed, "ABCDEFGH");
+
+        let owned = Box::<DeviceId>::from("ABCDEFGH");
+        assert_eq!(Box::<str>::from(&*owned), boxed);
+    }
+
+    #[test]
+    fn device_id_prefix_range() {
+        let mut map = BTreeMap::new();
+        for (i, id) in ["ABC", "ABCDEFGH", "ABD", "AB", "ABCZ", "abc"].iter().enumerate() {
+            map.insert(Box::<DeviceId>::from(*id), i);
+        }
+
+        // The ordering of device IDs matches the one of string slices, so this finds all device
+        // IDs starting with `ABC`.
+        let with_prefix: Vec<_> = map
+            .range::<str, _>((Bound::Included("ABC"), Bound::Excluded("ABC\u{10FFFF}")))
+            .map(|(id, _)| id.as_str())
+            .collect();
+        assert_eq!(with_prefix, ["ABC", "ABCDEFGH", "ABCZ"]);
+
+        let device_id = <&DeviceId>::from("ABCDEFGH");
+        assert_eq!(Borrow::<str>::borrow(device_id), "ABCDEFGH");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_device_id() {
+        assert_eq!(to_json_value(DeviceId::from_borrowed("ABCDEFGH")).unwrap(), json!("ABCDEFGH"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_device_id() {
+        let device_id: Box<DeviceId> = from_json_value(json!("ABCDEFGH")).unwrap();
+        assert_eq!(device_id, "ABCDEFGH");
+    }
 }
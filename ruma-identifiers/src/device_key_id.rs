@@ -1,12 +1,20 @@
 //! Identifiers for device keys for end-to-end encryption.
 
-use std::{convert::TryInto, num::NonZeroU8, str::FromStr};
+use std::{cmp::Ordering, convert::TryFrom, num::NonZeroU8, ops::Deref};
 
 use ruma_identifiers_validation::{crypto_algorithms::DeviceKeyAlgorithm, Error};
 
-use crate::DeviceId;
+use crate::{cmp_key_id_parts, key_id_from_parts, key_id_with_identifier, DeviceId};
 
 /// A key algorithm and a device id, combined with a ':'
+///
+/// Only the first colon separates the algorithm from the device ID. Device IDs are opaque, so the
+/// device ID part may itself contain colons, e.g. `ed25519:dev:1` has the algorithm `ed25519` and
+/// the device ID `dev:1`. Use [`device_id_segments`](#method.device_id_segments) to split it
+/// further.
+///
+/// Cloning a `DeviceKeyId` copies the key ID into a new allocation. If key IDs are cloned a lot,
+/// consider sharing them through an `Arc<DeviceKeyId>` instead.
 #[derive(Clone, Debug)]
 pub struct DeviceKeyId {
     full_id: Box<str>,
@@ -15,31 +23,232 @@ pub struct DeviceKeyId {
 
 impl DeviceKeyId {
     /// Create a `DeviceKeyId` from a `DeviceKeyAlgorithm` and a `DeviceId`.
+    ///
+    /// Panics if the algorithm name is empty, longer than 255 bytes or contains a colon, or if the
+    /// device ID is empty. Use [`try_from_parts`](#method.try_from_parts) for a non-panicking
+    /// version.
     pub fn from_parts(algorithm: DeviceKeyAlgorithm, device_id: &DeviceId) -> Self {
-        let algorithm: &str = algorithm.as_ref();
-        let device_id: &str = device_id.as_ref();
+        Self::try_from_parts(algorithm, device_id).expect("valid algorithm name and device ID")
+    }
+
+    /// Checks whether the given string is a valid `DeviceKeyId`, without creating one.
+    ///
+    /// This doesn't allocate, unlike `DeviceKeyId::try_from`.
+    pub fn validate_only(s: &str) -> Result<(), Error> {
+        ruma_identifiers_validation::device_key_id::validate(s).map(|_| ())
+    }
+
+    /// Creates a `DeviceKeyId` from bytes that haven't been checked to be valid UTF-8 yet.
+    ///
+    /// Device IDs are opaque, so unlike for `ServerKeyId::try_from_bytes`, checking for valid
+    /// UTF-8 is the only pass over the device ID either way. This exists for symmetry, so both
+    /// kinds of key IDs can be parsed from bytes the same way.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (key_id, colon_idx) =
+            ruma_identifiers_validation::device_key_id::validate_bytes(bytes)?;
+        Ok(Self { full_id: key_id.into(), colon_idx })
+    }
+
+    /// Creates a builder for a `DeviceKeyId`, for setting the algorithm and the device ID
+    /// separately.
+    ///
+    /// ```
+    /// # use ruma_identifiers::{DeviceKeyAlgorithm, DeviceKeyId};
+    /// let key_id = DeviceKeyId::builder()
+    ///     .algorithm(DeviceKeyAlgorithm::Ed25519)
+    ///     .device_id("JLAFKJWSCS")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(key_id, "ed25519:JLAFKJWSCS");
+    /// ```
+    pub fn builder() -> DeviceKeyIdBuilder {
+        DeviceKeyIdBuilder::default()
+    }
 
-        let mut res = String::with_capacity(algorithm.len() + 1 + device_id.len());
-        res.push_str(algorithm);
-        res.push(':');
-        res.push_str(device_id);
+    /// Create a `DeviceKeyId` from a `DeviceKeyAlgorithm` and a `DeviceId`.
+    ///
+    /// Fails if the algorithm name is empty, longer than 255 bytes or contains a colon. Only the
+    /// part of a key ID before its first colon is its algorithm, so an algorithm with a colon, like
+    /// a custom one created from `"io.ruma:test"`, can't be part of a key ID.
+    ///
+    /// Fails with `Error::InvalidKeyVersion` if the device ID is empty, like
+    /// `DeviceKeyId::try_from` does.
+    pub fn try_from_parts(
+        algorithm: DeviceKeyAlgorithm,
+        device_id: &DeviceId,
+    ) -> Result<Self, Error> {
+        if device_id.is_empty() {
+            return Err(Error::InvalidKeyVersion);
+        }
 
-        let colon_idx =
-            NonZeroU8::new(algorithm.len().try_into().expect("no algorithm name len > 255"))
-                .expect("no empty algorithm name");
+        let (full_id, colon_idx) = key_id_from_parts(algorithm.as_ref(), device_id.as_ref())?;
+        Ok(DeviceKeyId { full_id, colon_idx })
+    }
 
-        DeviceKeyId { full_id: res.into(), colon_idx }
+    /// Creates a `DeviceKeyId` from a string, additionally requiring the device ID part to be
+    /// unpadded base64, i.e. to only contain the characters `[A-Za-z0-9_-]`.
+    ///
+    /// The identifiers of one-time keys, like `AAAAHg` in `signed_curve25519:AAAAHg`, have this
+    /// format. Fails with `Error::InvalidKeyVersion` if the identifier contains other characters,
+    /// including `=` padding.
+    pub fn try_from_base64_identifier(s: &str) -> Result<Self, Error> {
+        let colon_idx = ruma_identifiers_validation::device_key_id::validate_base64_identifier(s)?;
+        Ok(DeviceKeyId { full_id: s.into(), colon_idx })
+    }
+
+    /// Creates a `DeviceKeyId` from a string that is already known to be a valid device key ID,
+    /// without checking it again.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU8;
+    /// # use ruma_identifiers::DeviceKeyId;
+    /// let colon_idx = NonZeroU8::new(7).unwrap();
+    /// let key_id = unsafe { DeviceKeyId::from_str_unchecked("ed25519:JLAFKJWSCS", colon_idx) };
+    /// assert_eq!(key_id.as_algorithm_str(), "ed25519");
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `s` must be a valid device key ID and `colon_idx` must be the index of its first colon,
+    /// i.e. `colon_idx` must be what `DeviceKeyId::try_from(s)` would compute. Other methods of
+    /// the returned `DeviceKeyId` rely on this and may misbehave otherwise. This is only checked
+    /// in builds with debug assertions enabled.
+    pub unsafe fn from_str_unchecked(s: &str, colon_idx: NonZeroU8) -> Self {
+        debug_assert_eq!(
+            ruma_identifiers_validation::device_key_id::validate(s),
+            Ok(colon_idx),
+            "invalid device key ID passed to `DeviceKeyId::from_str_unchecked`"
+        );
+
+        DeviceKeyId { full_id: s.into(), colon_idx }
     }
 
     /// Returns key algorithm of the device key ID.
+    ///
+    /// This never panics: algorithm names that aren't known to ruma are returned as a custom
+    /// `DeviceKeyAlgorithm` holding the original string.
     pub fn algorithm(&self) -> DeviceKeyAlgorithm {
-        DeviceKeyAlgorithm::from_str(&self.full_id[..self.colon_idx.get() as usize]).unwrap()
+        self.as_algorithm_str().into()
+    }
+
+    /// Returns the key algorithm of the device key ID as a string slice, without parsing it.
+    pub fn as_algorithm_str(&self) -> &str {
+        &self.full_id[..self.colon_idx.get() as usize]
+    }
+
+    /// Whether this key ID uses the given algorithm.
+    ///
+    /// This compares the algorithm's string representation with the raw algorithm of the key ID,
+    /// so the key ID's algorithm doesn't have to be parsed.
+    pub fn matches_algorithm(&self, algorithm: &DeviceKeyAlgorithm) -> bool {
+        self.algorithm_str_eq(algorithm.as_str())
+    }
+
+    /// Whether the algorithm of this key ID is exactly the given string.
+    ///
+    /// Like [`matches_algorithm`](#method.matches_algorithm), but for algorithm names that are
+    /// only available as strings, e.g. because they come from a configuration file.
+    pub fn algorithm_str_eq(&self, algorithm: &str) -> bool {
+        self.as_algorithm_str() == algorithm
     }
 
     /// Returns device ID of the device key ID.
     pub fn device_id(&self) -> &DeviceId {
         (&self.full_id[self.colon_idx.get() as usize + 1..]).into()
     }
+
+    /// Returns an iterator over the colon-separated segments of the device ID.
+    ///
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use ruma_identifiers::DeviceKeyId;
+    /// let key_id = DeviceKeyId::try_from("ed25519:dev:1").unwrap();
+    /// assert_eq!(key_id.device_id_segments().collect::<Vec<_>>(), ["dev", "1"]);
+    /// ```
+    pub fn device_id_segments(&self) -> impl Iterator<Item = &str> {
+        self.device_id().as_str().split(':')
+    }
+
+    /// Creates a new `DeviceKeyId` with the same algorithm as this one, but the given device ID.
+    ///
+    /// Fails with `Error::InvalidKeyVersion` if the device ID is empty.
+    pub fn with_device_id(&self, device_id: &DeviceId) -> Result<Self, Error> {
+        if device_id.is_empty() {
+            return Err(Error::InvalidKeyVersion);
+        }
+
+        let full_id = key_id_with_identifier(&self.full_id, self.colon_idx, device_id.as_str());
+        Ok(DeviceKeyId { full_id, colon_idx: self.colon_idx })
+    }
+
+    /// Compares this `DeviceKeyId` to another one by algorithm, then by device ID.
+    ///
+    /// Unlike the `Ord` implementation, which compares the raw strings, device IDs are compared
+    /// numerically if both of them are integers. This is meant for displaying lists of keys.
+    pub fn cmp_by_parts(&self, other: &Self) -> Ordering {
+        cmp_key_id_parts(
+            (self.as_algorithm_str(), self.device_id().as_str()),
+            (other.as_algorithm_str(), other.device_id().as_str()),
+        )
+    }
+
+    /// Returns the key algorithm and the device ID of the device key ID as string slices, without
+    /// parsing them.
+    pub fn as_parts(&self) -> (&str, &str) {
+        let colon_idx = self.colon_idx.get() as usize;
+        (&self.full_id[..colon_idx], &self.full_id[colon_idx + 1..])
+    }
+
+    /// Like [`as_parts`](#method.as_parts), but additionally returns the byte index of the colon
+    /// separating the key algorithm and the device ID.
+    ///
+    /// This is meant for feeding the parts of the key ID into a hasher without parsing them.
+    pub fn as_parts_with_colon_idx(&self) -> (&str, &str, usize) {
+        let (algorithm, device_id) = self.as_parts();
+        (algorithm, device_id, self.colon_idx.get() as usize)
+    }
+
+    /// Returns both the key algorithm and the device ID of the device key ID.
+    pub fn parts(&self) -> (DeviceKeyAlgorithm, &DeviceId) {
+        (self.algorithm(), self.device_id())
+    }
+}
+
+/// A builder for a `DeviceKeyId`, created with [`DeviceKeyId::builder`].
+///
+/// [`DeviceKeyId::builder`]: struct.DeviceKeyId.html#method.builder
+#[derive(Clone, Debug, Default)]
+pub struct DeviceKeyIdBuilder {
+    algorithm: Option<DeviceKeyAlgorithm>,
+    device_id: Option<Box<DeviceId>>,
+}
+
+impl DeviceKeyIdBuilder {
+    /// Sets the key algorithm.
+    pub fn algorithm(mut self, algorithm: DeviceKeyAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets the device ID.
+    pub fn device_id(mut self, device_id: impl Into<Box<DeviceId>>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    /// Creates the `DeviceKeyId`.
+    ///
+    /// Fails with `Error::EmptyKeyAlgorithm` if no algorithm was set and with
+    /// `Error::InvalidKeyVersion` if no device ID or an empty one was set. Fails like
+    /// [`DeviceKeyId::try_from_parts`] otherwise.
+    ///
+    /// [`DeviceKeyId::try_from_parts`]: struct.DeviceKeyId.html#method.try_from_parts
+    pub fn build(self) -> Result<DeviceKeyId, Error> {
+        let algorithm = self.algorithm.ok_or(Error::EmptyKeyAlgorithm)?;
+        let device_id = self.device_id.ok_or(Error::InvalidKeyVersion)?;
+
+        DeviceKeyId::try_from_parts(algorithm, &device_id)
+    }
 }
 
 fn try_from<S>(key_id: S) -> Result<DeviceKeyId, Error>
@@ -51,27 +260,81 @@ where
 }
 
 common_impls!(DeviceKeyId, try_from, "Device key ID with algorithm and device ID");
+partial_eq_string!(@ref, DeviceKeyId);
+partial_eq_string!(@owned, DeviceKeyId);
+
+impl Deref for DeviceKeyId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for DeviceKeyId {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Like [`DeviceKeyId::try_from_bytes`](struct.DeviceKeyId.html#method.try_from_bytes), but reuses
+/// the allocation of the given bytes.
+impl TryFrom<Vec<u8>> for DeviceKeyId {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Error> {
+        try_from(String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+    }
+}
+
+/// Creates a `DeviceKeyId` from an `(algorithm, device_id)` pair, like
+/// [`DeviceKeyId::try_from_parts`](struct.DeviceKeyId.html#method.try_from_parts).
+impl TryFrom<(DeviceKeyAlgorithm, &DeviceId)> for DeviceKeyId {
+    type Error = Error;
+
+    fn try_from((algorithm, device_id): (DeviceKeyAlgorithm, &DeviceId)) -> Result<Self, Error> {
+        Self::try_from_parts(algorithm, device_id)
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use std::convert::TryFrom;
+    use std::{
+        borrow::Cow,
+        collections::{hash_map::DefaultHasher, BTreeMap},
+        convert::TryFrom,
+        hash::{Hash, Hasher},
+    };
 
     use ruma_identifiers_validation::{crypto_algorithms::DeviceKeyAlgorithm, Error};
     #[cfg(feature = "serde")]
     use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
 
     use super::DeviceKeyId;
+    use crate::DeviceId;
 
     #[test]
     fn convert_device_key_id() {
         assert_eq!(
             DeviceKeyId::try_from("ed25519:JLAFKJWSCS")
                 .expect("Failed to create device key ID.")
-                .as_ref(),
+                .as_str(),
             "ed25519:JLAFKJWSCS"
         );
     }
 
+    #[test]
+    fn device_key_id_as_bytes_ref() {
+        fn len(bytes: impl AsRef<[u8]>) -> usize {
+            bytes.as_ref().len()
+        }
+
+        let key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+        assert_eq!(AsRef::<[u8]>::as_ref(&key_id), b"ed25519:JLAFKJWSCS");
+        assert_eq!(len(&key_id), 18);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serialize_device_key_id() {
@@ -93,28 +356,73 @@ mod test {
 
     #[test]
     fn missing_key_algorithm() {
-        assert_eq!(DeviceKeyId::try_from(":JLAFKJWSCS").unwrap_err(), Error::UnknownKeyAlgorithm);
+        assert_eq!(DeviceKeyId::try_from(":JLAFKJWSCS").unwrap_err(), Error::EmptyKeyAlgorithm);
     }
 
     #[test]
     fn missing_delimiter() {
         assert_eq!(
             DeviceKeyId::try_from("ed25519|JLAFKJWSCS").unwrap_err(),
-            Error::MissingDeviceKeyDelimiter,
+            Error::MissingDeviceKeyDelimiter { len: 18 },
         );
     }
 
     #[test]
-    fn unknown_key_algorithm() {
+    fn missing_delimiter_reports_input_length() {
         assert_eq!(
-            DeviceKeyId::try_from("signed_curve25510:JLAFKJWSCS").unwrap_err(),
-            Error::UnknownKeyAlgorithm,
+            DeviceKeyId::try_from("").unwrap_err(),
+            Error::MissingDeviceKeyDelimiter { len: 0 }
+        );
+        assert_eq!(
+            DeviceKeyId::try_from("JLAFKJWSCS").unwrap_err(),
+            Error::MissingDeviceKeyDelimiter { len: 10 }
         );
     }
 
     #[test]
-    fn empty_device_id_ok() {
-        assert!(DeviceKeyId::try_from("ed25519:").is_ok());
+    fn custom_key_algorithm() {
+        let device_key_id = DeviceKeyId::try_from("signed_curve25510:JLAFKJWSCS").unwrap();
+        assert_eq!(device_key_id.algorithm().as_str(), "signed_curve25510");
+        assert_eq!(device_key_id.as_str(), "signed_curve25510:JLAFKJWSCS");
+    }
+
+    #[test]
+    fn key_algorithm_too_long() {
+        let algorithm = "a".repeat(300);
+        let key_id = format!("{}:JLAFKJWSCS", algorithm);
+        assert_eq!(DeviceKeyId::try_from(key_id).unwrap_err(), Error::AlgorithmNameTooLong);
+        assert_eq!(
+            DeviceKeyId::try_from_parts(algorithm.as_str().into(), "JLAFKJWSCS".into())
+                .unwrap_err(),
+            Error::AlgorithmNameTooLong
+        );
+    }
+
+    #[test]
+    fn empty_device_id() {
+        assert_eq!(DeviceKeyId::try_from("ed25519:").unwrap_err(), Error::InvalidKeyVersion);
+    }
+
+    #[test]
+    fn parse_device_key_id() {
+        let key_id = "ed25519:JLAFKJ".parse::<DeviceKeyId>().unwrap();
+        assert_eq!(key_id.algorithm(), DeviceKeyAlgorithm::Ed25519);
+        assert_eq!(key_id.device_id(), "JLAFKJ");
+
+        assert_eq!("ed25519:".parse::<DeviceKeyId>().unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(
+            "ed25519".parse::<DeviceKeyId>().unwrap_err(),
+            Error::MissingDeviceKeyDelimiter { len: 7 }
+        );
+        assert_eq!(":JLAFKJ".parse::<DeviceKeyId>().unwrap_err(), Error::EmptyKeyAlgorithm);
+    }
+
+    #[test]
+    fn trailing_garbage_without_delimiter() {
+        assert_eq!(
+            DeviceKeyId::try_from("ed25519JLAFKJWSCS").unwrap_err(),
+            Error::MissingDeviceKeyDelimiter { len: 17 },
+        );
     }
 
     #[test]
@@ -123,9 +431,251 @@ mod test {
         assert_eq!(device_key_id.algorithm(), DeviceKeyAlgorithm::Ed25519);
     }
 
+    #[test]
+    fn curve25519_key_algorithm() {
+        let device_key_id = DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap();
+        assert_eq!(device_key_id.algorithm(), DeviceKeyAlgorithm::Curve25519);
+        assert_eq!(device_key_id.device_id(), "JLAFKJWSCS");
+
+        let device_key_id = DeviceKeyId::try_from("signed_curve25519:JLAFKJWSCS").unwrap();
+        assert_eq!(device_key_id.algorithm(), DeviceKeyAlgorithm::SignedCurve25519);
+        assert_eq!(device_key_id.device_id(), "JLAFKJWSCS");
+    }
+
+    #[test]
+    fn one_time_key_id() {
+        let key_id = DeviceKeyId::try_from("signed_curve25519:AAAAHg").unwrap();
+        assert_eq!(key_id.algorithm(), DeviceKeyAlgorithm::SignedCurve25519);
+        assert_ne!(key_id.algorithm(), DeviceKeyAlgorithm::Curve25519);
+        assert_eq!(key_id.device_id(), "AAAAHg");
+        assert_eq!(key_id.to_string(), "signed_curve25519:AAAAHg");
+
+        let rebuilt = DeviceKeyId::from_parts(key_id.algorithm(), key_id.device_id());
+        assert_eq!(rebuilt, key_id);
+    }
+
+    #[test]
+    fn device_key_id_with_base64_identifier() {
+        let key_id = DeviceKeyId::try_from_base64_identifier("signed_curve25519:AAAAHg").unwrap();
+        assert_eq!(key_id.algorithm(), DeviceKeyAlgorithm::SignedCurve25519);
+        assert_eq!(key_id.device_id(), "AAAAHg");
+
+        assert_eq!(
+            DeviceKeyId::try_from_base64_identifier("signed_curve25519:AAAAHg=").unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+        assert!(DeviceKeyId::try_from("signed_curve25519:AAAAHg=").is_ok());
+    }
+
+    #[test]
+    fn device_key_id_from_parts_with_colon_in_algorithm() {
+        let algorithm = DeviceKeyAlgorithm::from("io.ruma:test");
+        assert_eq!(
+            DeviceKeyId::try_from_parts(algorithm, "JLAFKJWSCS".into()).unwrap_err(),
+            Error::InvalidKeyAlgorithm
+        );
+    }
+
+    #[test]
+    #[should_panic = "valid algorithm name"]
+    fn device_key_id_from_parts_with_colon_in_algorithm_panics() {
+        DeviceKeyId::from_parts(DeviceKeyAlgorithm::from("io.ruma:test"), "JLAFKJWSCS".into());
+    }
+
+    #[test]
+    fn device_key_id_from_parts() {
+        let device_key_id =
+            DeviceKeyId::try_from_parts(DeviceKeyAlgorithm::Curve25519, "JLAFKJWSCS".into())
+                .unwrap();
+        assert_eq!(device_key_id, DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap());
+    }
+
+    #[test]
+    fn device_key_id_from_parts_with_empty_device_id() {
+        assert_eq!(
+            DeviceKeyId::try_from_parts(DeviceKeyAlgorithm::Ed25519, "".into()).unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+    }
+
+    #[test]
+    fn device_key_id_from_tuple() {
+        let device_id: &DeviceId = "JLAFKJWSCS".into();
+        let device_key_id =
+            DeviceKeyId::try_from((DeviceKeyAlgorithm::Ed25519, device_id)).unwrap();
+        assert_eq!(device_key_id, "ed25519:JLAFKJWSCS");
+
+        assert_eq!(
+            DeviceKeyId::try_from((DeviceKeyAlgorithm::from(""), device_id)).unwrap_err(),
+            Error::EmptyKeyAlgorithm
+        );
+    }
+
+    #[test]
+    fn matches_algorithm() {
+        let key_id = DeviceKeyId::try_from("signed_curve25519:AAAAHg").unwrap();
+        assert!(key_id.matches_algorithm(&DeviceKeyAlgorithm::SignedCurve25519));
+        assert!(!key_id.matches_algorithm(&DeviceKeyAlgorithm::Curve25519));
+        assert!(!key_id.matches_algorithm(&"signed".into()));
+    }
+
+    #[test]
+    fn build_device_key_id() {
+        let key_id = DeviceKeyId::builder()
+            .device_id("JLAFKJWSCS")
+            .algorithm(DeviceKeyAlgorithm::Curve25519)
+            .build()
+            .unwrap();
+        assert_eq!(key_id, "curve25519:JLAFKJWSCS");
+    }
+
+    #[test]
+    fn build_device_key_id_with_missing_parts() {
+        let missing_device_id = DeviceKeyId::builder().algorithm(DeviceKeyAlgorithm::Ed25519);
+        assert_eq!(missing_device_id.clone().build().unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(missing_device_id.device_id("").build().unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(
+            DeviceKeyId::builder().device_id("JLAFKJWSCS").build().unwrap_err(),
+            Error::EmptyKeyAlgorithm
+        );
+    }
+
+    #[test]
+    fn validate_only() {
+        assert_eq!(DeviceKeyId::validate_only("ed25519:JLAFKJWSCS"), Ok(()));
+        assert_eq!(
+            DeviceKeyId::validate_only("ed25519"),
+            Err(Error::MissingDeviceKeyDelimiter { len: 7 })
+        );
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let key_id = DeviceKeyId::try_from_bytes(b"ed25519:JLAFKJWSCS").unwrap();
+        assert_eq!(key_id.as_parts(), ("ed25519", "JLAFKJWSCS"));
+        assert_eq!(DeviceKeyId::try_from(b"ed25519:JLAFKJWSCS".to_vec()).unwrap(), key_id);
+
+        assert_eq!(
+            DeviceKeyId::try_from_bytes(b"ed25519:JLAF\xffKJWSCS").unwrap_err(),
+            Error::InvalidUtf8
+        );
+        assert_eq!(
+            DeviceKeyId::try_from(b"ed25519".to_vec()).unwrap_err(),
+            Error::MissingDeviceKeyDelimiter { len: 7 }
+        );
+    }
+
+    #[test]
+    fn algorithm_str_eq() {
+        assert!(DeviceKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ed25519"));
+        assert!(!DeviceKeyId::try_from("curve25519:1").unwrap().algorithm_str_eq("ed25519"));
+        assert!(!DeviceKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ED25519"));
+        assert!(!DeviceKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ed25519:1"));
+    }
+
+    #[test]
+    fn device_key_id_as_parts() {
+        let device_key_id = DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap();
+        assert_eq!(device_key_id.as_parts(), ("curve25519", "JLAFKJWSCS"));
+        assert_eq!(device_key_id.as_parts_with_colon_idx(), ("curve25519", "JLAFKJWSCS", 10));
+    }
+
+    #[test]
+    fn device_key_id_parts() {
+        let device_key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+        assert_eq!(device_key_id.as_algorithm_str(), "ed25519");
+        assert_eq!(device_key_id.parts(), (DeviceKeyAlgorithm::Ed25519, "JLAFKJWSCS".into()));
+    }
+
+    #[test]
+    fn device_id_with_colons() {
+        let key_id = DeviceKeyId::try_from("ed25519:dev:1").unwrap();
+        assert_eq!(key_id.algorithm(), DeviceKeyAlgorithm::Ed25519);
+        assert_eq!(key_id.device_id(), "dev:1");
+        assert_eq!(key_id.device_id_segments().collect::<Vec<_>>(), ["dev", "1"]);
+    }
+
+    #[test]
+    fn device_id_segments_without_colons() {
+        let key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+        assert_eq!(key_id.device_id_segments().collect::<Vec<_>>(), ["JLAFKJWSCS"]);
+    }
+
+    #[test]
+    fn device_key_id_with_device_id() {
+        let key_id = DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap();
+        let other_key_id = key_id.with_device_id("ABCDEFGH".into()).unwrap();
+
+        assert_eq!(other_key_id, "curve25519:ABCDEFGH");
+        assert_eq!(other_key_id.algorithm(), DeviceKeyAlgorithm::Curve25519);
+        assert_eq!(other_key_id.device_id(), "ABCDEFGH");
+    }
+
+    #[test]
+    fn device_key_id_with_empty_device_id() {
+        let key_id = DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap();
+        assert_eq!(key_id.with_device_id("".into()).unwrap_err(), Error::InvalidKeyVersion);
+    }
+
+    #[test]
+    fn deref_to_str() {
+        let key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+        assert!(key_id.starts_with("ed25519:"));
+        assert_eq!(key_id.len(), 18);
+    }
+
+    #[test]
+    fn borrow_device_key_id_as_str() {
+        let mut map = BTreeMap::new();
+        map.insert(DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap(), "sig");
+        assert_eq!(map.get("ed25519:JLAFKJWSCS"), Some(&"sig"));
+    }
+
+    #[test]
+    fn hash_matches_str_hash() {
+        fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        // Required for the `Borrow<str>` implementation to be used for lookups in hash maps.
+        let samples = [
+            "ed25519:JLAFKJWSCS",
+            "curve25519:ABCDEFGH",
+            "signed_curve25519:AAAAHg",
+            "io.ruma.test:DEVICE:WITH:COLONS",
+            "äöü:ÄÖÜ",
+        ];
+        for s in &samples {
+            let key_id = DeviceKeyId::try_from(*s).unwrap();
+            assert_eq!(hash_of(&key_id), hash_of(*s), "{}", s);
+            assert_eq!(hash_of(&key_id), hash_of(key_id.as_str()), "{}", s);
+        }
+    }
+
     #[test]
     fn valid_device_id() {
         let device_key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
         assert_eq!(device_key_id.device_id(), "JLAFKJWSCS");
     }
+
+    #[test]
+    fn compare_with_owned_strings() {
+        let key_id = DeviceKeyId::try_from("ed25519:JLAFKJWSCS").unwrap();
+        let boxed: Box<str> = "ed25519:JLAFKJWSCS".into();
+        let borrowed_cow = Cow::Borrowed("ed25519:JLAFKJWSCS");
+        let owned_cow: Cow<'_, str> = Cow::Owned("ed25519:JLAFKJWSCS".to_owned());
+
+        assert_eq!(key_id, boxed);
+        assert_eq!(boxed, key_id);
+        assert_eq!(key_id, borrowed_cow);
+        assert_eq!(borrowed_cow, key_id);
+        assert_eq!(key_id, owned_cow);
+        assert_eq!(owned_cow, key_id);
+
+        let other: Box<str> = "ed25519:OTHER".into();
+        assert_ne!(key_id, other);
+        assert_ne!(Cow::Borrowed("curve25519:JLAFKJWSCS"), key_id);
+    }
 }
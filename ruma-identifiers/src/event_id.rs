@@ -81,6 +81,31 @@ impl EventId {
         self.colon_idx
             .map(|idx| <&ServerName>::try_from(&self.full_id[idx.get() as usize + 1..]).unwrap())
     }
+
+    /// Returns the format of the event ID.
+    ///
+    /// Event IDs containing a server name are in the original format used by room versions 1 and
+    /// 2, all others are in the format used by room versions 3 and later.
+    pub fn grammar(&self) -> EventIdVersion {
+        match self.colon_idx {
+            Some(_) => EventIdVersion::V1,
+            None => EventIdVersion::V3,
+        }
+    }
+}
+
+/// The format of an event ID, as used by different room versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventIdVersion {
+    /// The original format as used by Matrix room versions 1 and 2, e.g. `$h29iv0s8:example.com`.
+    V1,
+
+    /// The format used by Matrix room versions 3 and later, e.g.
+    /// `$acR1l0raoZnm60CBwAVgqbZqoO/mYU81xysh1u7XcJk`.
+    ///
+    /// This is a Base64-encoded hash of the event, without a server name.
+    V3,
 }
 
 /// Attempts to create a new Matrix event ID from a string representation.
@@ -104,8 +129,8 @@ mod tests {
     #[cfg(feature = "serde")]
     use serde_json::{from_str, to_string};
 
-    use super::EventId;
-    use crate::Error;
+    use super::{EventId, EventIdVersion};
+    use crate::{Error, ServerNameError};
 
     #[test]
     fn valid_original_event_id() {
@@ -137,6 +162,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn original_event_id_parts() {
+        let event_id = EventId::try_from("$39hvsi03hlne:example.com").unwrap();
+        assert_eq!(event_id.grammar(), EventIdVersion::V1);
+        assert_eq!(event_id.localpart(), "39hvsi03hlne");
+        assert_eq!(event_id.server_name().unwrap(), "example.com");
+    }
+
+    #[test]
+    fn base64_event_id_parts() {
+        let event_id = EventId::try_from("$acR1l0raoZnm60CBwAVgqbZqoO/mYU81xysh1u7XcJk").unwrap();
+        assert_eq!(event_id.grammar(), EventIdVersion::V3);
+        assert_eq!(event_id.localpart(), "acR1l0raoZnm60CBwAVgqbZqoO/mYU81xysh1u7XcJk");
+        assert_eq!(event_id.server_name(), None);
+    }
+
     #[cfg(feature = "rand")]
     #[test]
     fn generate_random_valid_event_id() {
@@ -264,14 +305,17 @@ mod tests {
 
     #[test]
     fn invalid_event_id_host() {
-        assert_eq!(EventId::try_from("$39hvsi03hlne:/").unwrap_err(), Error::InvalidServerName);
+        assert_eq!(
+            EventId::try_from("$39hvsi03hlne:/").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
     }
 
     #[test]
     fn invalid_event_id_port() {
         assert_eq!(
             EventId::try_from("$39hvsi03hlne:example.com:notaport").unwrap_err(),
-            Error::InvalidServerName
+            Error::InvalidServerName(ServerNameError::InvalidPort)
         );
     }
 }
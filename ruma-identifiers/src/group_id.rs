@@ -0,0 +1,122 @@
+//! Matrix group identifiers.
+
+use std::{convert::TryFrom, num::NonZeroU8};
+
+use crate::{Error, ServerName};
+
+/// A Matrix group ID.
+///
+/// Groups, also known as communities, are not part of the stable Matrix specification anymore,
+/// but some servers still use them.
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use ruma_identifiers::GroupId;
+/// assert_eq!(
+///     GroupId::try_from("+mygroup:example.org").unwrap().as_ref(),
+///     "+mygroup:example.org"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct GroupId {
+    full_id: Box<str>,
+    colon_idx: NonZeroU8,
+}
+
+impl GroupId {
+    /// Returns the group's localpart.
+    pub fn localpart(&self) -> &str {
+        &self.full_id[1..self.colon_idx.get() as usize]
+    }
+
+    /// Returns the server name of the group ID.
+    pub fn server_name(&self) -> &ServerName {
+        <&ServerName>::try_from(&self.full_id[self.colon_idx.get() as usize + 1..]).unwrap()
+    }
+}
+
+/// Attempts to create a new Matrix group ID from a string representation.
+///
+/// The string must include the leading + sigil, the localpart, a literal colon, and a server name.
+fn try_from<S>(group_id: S) -> Result<GroupId, Error>
+where
+    S: AsRef<str> + Into<Box<str>>,
+{
+    let colon_idx = ruma_identifiers_validation::group_id::validate(group_id.as_ref())?;
+    Ok(GroupId { full_id: group_id.into(), colon_idx })
+}
+
+common_impls!(GroupId, try_from, "a Matrix group ID");
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    #[cfg(feature = "serde")]
+    use serde_json::{from_str, to_string};
+
+    use super::GroupId;
+    use crate::{Error, ServerNameError};
+
+    #[test]
+    fn valid_group_id() {
+        let group_id =
+            GroupId::try_from("+mygroup:example.org").expect("Failed to create GroupId.");
+        assert_eq!(group_id.as_ref(), "+mygroup:example.org");
+        assert_eq!(group_id.localpart(), "mygroup");
+        assert_eq!(group_id.server_name(), "example.org");
+    }
+
+    #[test]
+    fn valid_group_id_with_explicit_port() {
+        let group_id = GroupId::try_from("+my_group:example.org:8448").unwrap();
+        assert_eq!(group_id.localpart(), "my_group");
+        assert_eq!(group_id.server_name(), "example.org:8448");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_valid_group_id() {
+        assert_eq!(
+            to_string(&GroupId::try_from("+mygroup:example.org").unwrap()).unwrap(),
+            r#""+mygroup:example.org""#
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_valid_group_id() {
+        assert_eq!(
+            from_str::<GroupId>(r#""+mygroup:example.org""#).unwrap(),
+            GroupId::try_from("+mygroup:example.org").unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_group_id_sigil() {
+        assert_eq!(GroupId::try_from("mygroup:example.org").unwrap_err(), Error::MissingSigil);
+        assert_eq!(GroupId::try_from("!mygroup:example.org").unwrap_err(), Error::MissingSigil);
+    }
+
+    #[test]
+    fn missing_group_id_delimiter() {
+        assert_eq!(GroupId::try_from("+mygroup").unwrap_err(), Error::MissingDelimiter);
+    }
+
+    #[test]
+    fn invalid_group_id_localpart() {
+        assert_eq!(
+            GroupId::try_from("+MyGroup:example.org").unwrap_err(),
+            Error::InvalidCharacters
+        );
+        assert_eq!(GroupId::try_from("+:example.org").unwrap_err(), Error::InvalidCharacters);
+    }
+
+    #[test]
+    fn invalid_group_id_host() {
+        assert_eq!(
+            GroupId::try_from("+mygroup:/").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
+    }
+}
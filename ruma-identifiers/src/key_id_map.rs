@@ -0,0 +1,128 @@
+//! (De)serialization helpers for maps of key IDs to signatures, for use with
+//! `#[serde(with = "…")]`.
+//!
+//! This is meant for the inner maps of signatures, i.e. `EntitySignatures<K>` with `K` being
+//! `DeviceKeyId` or `ServerKeyId`. Each key ID is validated exactly once while it is borrowed from
+//! the deserializer, without an intermediate `String`. Unlike the `Deserialize` implementation of
+//! `BTreeMap`, which keeps the last value for duplicate keys, deserialization fails if a key ID
+//! occurs more than once.
+//!
+//! ```
+//! # use ruma_identifiers::{EntitySignatures, ServerKeyId};
+//! # use serde1::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! # #[serde(crate = "serde1")]
+//! struct ServerSignature {
+//!     #[serde(with = "ruma_identifiers::key_id_map")]
+//!     signatures: EntitySignatures<ServerKeyId>,
+//! }
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use serde1::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::signatures::EntitySignatures;
+
+/// Serializes the map as an object with sorted key IDs.
+pub fn serialize<K, S>(signatures: &EntitySignatures<K>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize,
+    S: Serializer,
+{
+    serializer.collect_map(signatures.iter())
+}
+
+/// Deserializes the map from an object, failing on invalid or duplicate key IDs.
+pub fn deserialize<'de, K, D>(deserializer: D) -> Result<EntitySignatures<K>, D::Error>
+where
+    K: Ord + fmt::Display + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct KeyIdMapVisitor<K>(PhantomData<K>);
+
+    impl<'de, K> Visitor<'de> for KeyIdMapVisitor<K>
+    where
+        K: Ord + fmt::Display + Deserialize<'de>,
+    {
+        type Value = EntitySignatures<K>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map of key IDs to signatures")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut signatures = EntitySignatures::new();
+
+            while let Some((key_id, signature)) = map.next_entry::<K, String>()? {
+                if signatures.contains_key(&key_id) {
+                    return Err(de::Error::custom(format_args!("duplicate key ID `{}`", key_id)));
+                }
+
+                signatures.insert(key_id, signature);
+            }
+
+            Ok(signatures)
+        }
+    }
+
+    deserializer.deserialize_map(KeyIdMapVisitor(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde1::{Deserialize, Serialize};
+    use serde_json::{from_str as from_json_str, from_value as from_json_value, json, to_value};
+
+    use crate::{DeviceKeyAlgorithm, DeviceKeyId, EntitySignatures, ServerKeyId};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(crate = "serde1")]
+    struct ServerSignature {
+        #[serde(with = "super")]
+        signatures: EntitySignatures<ServerKeyId>,
+    }
+
+    #[test]
+    fn round_trip_server_key_id_map() {
+        let json = json!({ "signatures": { "ed25519:2": "sig2", "ed25519:1": "sig1" } });
+        let signature: ServerSignature = from_json_value(json.clone()).unwrap();
+
+        let keys: Vec<_> = signature.signatures.keys().map(ServerKeyId::as_str).collect();
+        assert_eq!(keys, ["ed25519:1", "ed25519:2"]);
+        assert_eq!(to_value(&signature).unwrap(), json);
+    }
+
+    #[test]
+    fn round_trip_device_key_id_map() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(crate = "serde1")]
+        struct DeviceSignature {
+            #[serde(with = "super")]
+            signatures: EntitySignatures<DeviceKeyId>,
+        }
+
+        let json = json!({ "signatures": { "ed25519:JLAFKJWSCS": "sig" } });
+        let signature: DeviceSignature = from_json_value(json.clone()).unwrap();
+
+        let key_id = DeviceKeyId::from_parts(DeviceKeyAlgorithm::Ed25519, "JLAFKJWSCS".into());
+        assert_eq!(signature.signatures[&key_id], "sig");
+        assert_eq!(to_value(&signature).unwrap(), json);
+    }
+
+    #[test]
+    fn invalid_key_id() {
+        let json = json!({ "signatures": { "ed25519": "sig" } });
+        assert!(from_json_value::<ServerSignature>(json).is_err());
+    }
+
+    #[test]
+    fn duplicate_key_id() {
+        let json = r#"{ "signatures": { "ed25519:1": "sig1", "ed25519:1": "sig2" } }"#;
+        let err = from_json_str::<ServerSignature>(json).unwrap_err();
+        assert!(err.to_string().starts_with("duplicate key ID `ed25519:1`"), "{}", err);
+    }
+}
@@ -9,38 +9,52 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-use std::convert::TryFrom;
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    convert::{TryFrom, TryInto},
+    num::NonZeroU8,
+};
 
-#[cfg(feature = "serde")]
-use serde1::de::{self, Deserialize as _, Deserializer, Unexpected};
+#[cfg(feature = "serde1")]
+use serde1::de::{self, Deserializer, Unexpected};
 
 #[doc(inline)]
 pub use crate::{
     device_id::{DeviceId, DeviceIdBox},
-    device_key_id::DeviceKeyId,
-    event_id::EventId,
+    device_key_id::{DeviceKeyId, DeviceKeyIdBuilder},
+    event_id::{EventId, EventIdVersion},
+    group_id::GroupId,
+    mxc_uri::{MxcUri, MxcUriBox},
     room_alias_id::RoomAliasId,
     room_id::RoomId,
     room_id_or_room_alias_id::RoomIdOrAliasId,
     room_version_id::RoomVersionId,
-    server_key_id::ServerKeyId,
+    server_key_id::{ServerKeyId, ServerKeyIdBuilder},
     server_name::{ServerName, ServerNameBox},
+    signatures::{add_signature, DeviceSignatures, EntitySignatures, ServerSignatures, Signatures},
     user_id::UserId,
 };
 #[doc(inline)]
 pub use ruma_identifiers_validation::{
     crypto_algorithms::{DeviceKeyAlgorithm, EventEncryptionAlgorithm, ServerKeyAlgorithm},
-    error::Error,
+    error::{Error, ErrorKind, ServerNameError},
 };
 
 #[macro_use]
 mod macros;
 
 pub mod device_id;
+#[cfg(feature = "serde1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod key_id_map;
+pub mod signatures;
 pub mod user_id;
 
 mod device_key_id;
 mod event_id;
+mod group_id;
+mod mxc_uri;
 mod room_alias_id;
 mod room_id;
 mod room_id_or_room_alias_id;
@@ -67,18 +81,117 @@ fn generate_localpart(length: usize) -> Box<str> {
         .into_boxed_str()
 }
 
+/// Joins a key algorithm and a key identifier with a ':' and returns the result along with the
+/// index of the colon.
+///
+/// Fails if the algorithm name is empty, longer than 255 bytes or contains a colon. Key IDs are
+/// split at their first colon, so the latter couldn't be parsed back into the same algorithm.
+fn key_id_from_parts(algorithm: &str, identifier: &str) -> Result<(Box<str>, NonZeroU8), Error> {
+    let colon_idx =
+        NonZeroU8::new(algorithm.len().try_into().map_err(|_| Error::AlgorithmNameTooLong)?)
+            .ok_or(Error::EmptyKeyAlgorithm)?;
+    if algorithm.contains(':') {
+        return Err(Error::InvalidKeyAlgorithm);
+    }
+
+    let mut res = String::with_capacity(algorithm.len() + 1 + identifier.len());
+    res.push_str(algorithm);
+    res.push(':');
+    res.push_str(identifier);
+    debug_assert_eq!(res.find(':'), Some(colon_idx.get() as usize));
+
+    Ok((res.into(), colon_idx))
+}
+
+/// Replaces everything after the colon of a key ID with `identifier`, keeping the algorithm.
+fn key_id_with_identifier(full_id: &str, colon_idx: NonZeroU8, identifier: &str) -> Box<str> {
+    let algorithm_and_colon = &full_id[..=colon_idx.get() as usize];
+
+    let mut res = String::with_capacity(algorithm_and_colon.len() + identifier.len());
+    res.push_str(algorithm_and_colon);
+    res.push_str(identifier);
+
+    res.into()
+}
+
+/// Compares two key IDs by algorithm, then by the part after the colon.
+///
+/// The parts after the colon are compared numerically if both are integers and as strings
+/// otherwise. Ties between numerically equal parts (like `1` and `01`) are broken by comparing
+/// them as strings.
+fn cmp_key_id_parts(a: (&str, &str), b: (&str, &str)) -> Ordering {
+    a.0.cmp(b.0).then_with(|| match (a.1.parse::<u64>(), b.1.parse::<u64>()) {
+        (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num).then_with(|| a.1.cmp(b.1)),
+        _ => a.1.cmp(b.1),
+    })
+}
+
+/// Truncates an identifier to at most `max_chars` characters for logging, appending `…` if
+/// anything was cut off.
+///
+/// This counts characters rather than bytes, so it never splits a multi-byte character.
+fn truncate_id(s: &str, max_chars: usize) -> Cow<'_, str> {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => Cow::Owned(format!("{}…", &s[..end])),
+        None => Cow::Borrowed(s),
+    }
+}
+
+/// Converts a byte slice to a string slice, for the `TryFrom<&[u8]>` implementations.
+fn str_from_utf8(bytes: &[u8]) -> Result<&str, Error> {
+    std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+}
+
+/// The maximum number of characters of an invalid ID that is included in deserialization errors.
+#[cfg(feature = "serde1")]
+const MAX_INVALID_ID_LEN_IN_ERROR: usize = 64;
+
 /// Deserializes any type of id using the provided TryFrom implementation.
 ///
 /// This is a helper function to reduce the boilerplate of the Deserialize implementations.
-#[cfg(feature = "serde")]
+///
+/// The string is validated while borrowed from the deserializer where possible, so the `TryFrom`
+/// implementation performs the only allocation. If validation fails, the error contains the
+/// invalid string, truncated to `MAX_INVALID_ID_LEN_IN_ERROR` characters like `truncate_id` does.
+#[cfg(feature = "serde1")]
 fn deserialize_id<'de, D, T>(deserializer: D, expected_str: &str) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
     T: for<'a> std::convert::TryFrom<&'a str>,
 {
-    std::borrow::Cow::<'_, str>::deserialize(deserializer).and_then(|v| {
-        T::try_from(&v).map_err(|_| de::Error::invalid_value(Unexpected::Str(&v), &expected_str))
-    })
+    struct IdVisitor<'a, T> {
+        expected_str: &'a str,
+        _phantom: std::marker::PhantomData<T>,
+    }
+
+    impl<'de, T> de::Visitor<'de> for IdVisitor<'_, T>
+    where
+        T: for<'a> std::convert::TryFrom<&'a str>,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.expected_str)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            T::try_from(v).map_err(|_| {
+                E::invalid_value(
+                    Unexpected::Str(&truncate_id(v, MAX_INVALID_ID_LEN_IN_ERROR)),
+                    &self,
+                )
+            })
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+            match std::str::from_utf8(v) {
+                Ok(s) => self.visit_str(s),
+                Err(_) => Err(E::invalid_value(Unexpected::Bytes(v), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_str(IdVisitor { expected_str, _phantom: std::marker::PhantomData })
 }
 
 /// Shorthand for `Box::<DeviceId>::from`.
@@ -108,6 +221,18 @@ macro_rules! event_id {
     };
 }
 
+/// Compile-time checked key version construction.
+///
+/// Key versions, like the version of a `ServerKeyId`, are represented as string slices. This
+/// checks that the given version only consists of the characters `[a-zA-Z0-9_]` and expands to
+/// the string literal.
+#[macro_export]
+macro_rules! key_version {
+    ($s:literal) => {
+        $crate::_macros::key_version!($crate, $s)
+    };
+}
+
 /// Compile-time checked `RoomAliasId` construction.
 #[macro_export]
 macro_rules! room_alias_id {
@@ -155,3 +280,103 @@ macro_rules! user_id {
         $crate::_macros::user_id!($crate, $s)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    #[cfg(feature = "serde")]
+    use serde_json::{from_value as from_json_value, json};
+
+    use super::{cmp_key_id_parts, key_id_from_parts, truncate_id, Error};
+    #[cfg(feature = "serde")]
+    use crate::{DeviceId, ServerKeyId, UserId};
+
+    #[test]
+    fn key_id_from_valid_parts() {
+        let (full_id, colon_idx) = key_id_from_parts("ed25519", "Abc_1").unwrap();
+        assert_eq!(&*full_id, "ed25519:Abc_1");
+        assert_eq!(colon_idx.get(), 7);
+    }
+
+    #[test]
+    fn cmp_key_id_parts_numerically() {
+        assert_eq!(cmp_key_id_parts(("ed25519", "2"), ("ed25519", "10")), Ordering::Less);
+        assert_eq!(cmp_key_id_parts(("ed25519", "01"), ("ed25519", "1")), Ordering::Less);
+        assert_eq!(cmp_key_id_parts(("ed25519", "1"), ("ed25519", "1")), Ordering::Equal);
+        assert_eq!(cmp_key_id_parts(("ed25519", "10"), ("ed25519", "a")), Ordering::Less);
+        assert_eq!(cmp_key_id_parts(("curve25519", "10"), ("ed25519", "2")), Ordering::Less);
+    }
+
+    #[test]
+    fn truncate_short_id() {
+        assert_eq!(truncate_id("@carl:example.com", 17), "@carl:example.com");
+        assert_eq!(truncate_id("@carl:example.com", 100), "@carl:example.com");
+        assert_eq!(truncate_id("", 0), "");
+    }
+
+    #[test]
+    fn truncate_long_id() {
+        assert_eq!(truncate_id("@carl:example.com", 5), "@carl…");
+        assert_eq!(truncate_id("@carl:example.com", 0), "…");
+        // Two-byte characters, which would be split by truncating after an odd number of bytes
+        assert_eq!(truncate_id("äöü.algorithm:1", 2), "äö…");
+    }
+
+    #[test]
+    fn key_id_from_parts_empty_algorithm() {
+        assert_eq!(key_id_from_parts("", "Abc_1").unwrap_err(), Error::EmptyKeyAlgorithm);
+    }
+
+    #[test]
+    fn key_id_from_parts_algorithm_with_colon() {
+        assert_eq!(key_id_from_parts("io.ruma:test", "1").unwrap_err(), Error::InvalidKeyAlgorithm);
+        assert_eq!(key_id_from_parts(":", "1").unwrap_err(), Error::InvalidKeyAlgorithm);
+    }
+
+    #[test]
+    fn key_id_from_parts_algorithm_too_long() {
+        let algorithm = "a".repeat(256);
+        assert_eq!(
+            key_id_from_parts(&algorithm, "Abc_1").unwrap_err(),
+            Error::AlgorithmNameTooLong
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_error_contains_invalid_id() {
+        let err = from_json_value::<ServerKeyId>(json!("ed25519:")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value: string \"ed25519:\", expected Key ID with algorithm and version"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_error_truncates_long_invalid_id() {
+        let long_id = format!("ed25519:{}", "ä".repeat(100));
+        let err = from_json_value::<ServerKeyId>(json!(long_id)).unwrap_err().to_string();
+
+        // The 8 characters of `ed25519:` and 56 two-byte characters
+        let expected_prefix = format!("ed25519:{}…", "ä".repeat(56));
+        assert!(err.contains(&format!("\"{}\"", expected_prefix)), "{}", err);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_id_from_bytes() {
+        use serde1::{
+            de::value::{BytesDeserializer, Error as ValueError},
+            Deserialize,
+        };
+
+        let bytes = |b| BytesDeserializer::<ValueError>::new(b);
+
+        assert_eq!(Box::<DeviceId>::deserialize(bytes(b"ABCDEF")).unwrap(), "ABCDEF");
+        assert_eq!(UserId::deserialize(bytes(b"@carl:example.com")).unwrap(), "@carl:example.com");
+        assert!(UserId::deserialize(bytes(b"@carl")).is_err());
+        assert!(Box::<DeviceId>::deserialize(bytes(b"ABC\xffDEF")).is_err());
+    }
+}
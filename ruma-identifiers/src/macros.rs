@@ -1,8 +1,10 @@
 /// Declares an item with a doc attribute computed by some macro expression.
 /// This allows documentation to be dynamically generated based on input.
 /// Necessary to work around https://github.com/rust-lang/rust/issues/52607.
+///
+/// Only takes a single item, so that it can have further attributes after the doc attribute.
 macro_rules! doc_concat {
-    ( $( #[doc = $doc:expr] $( $thing:tt )* )* ) => ( $( #[doc = $doc] $( $thing )* )* );
+    ( #[doc = $doc:expr] $( $thing:tt )* ) => ( #[doc = $doc] $( $thing )* );
 }
 
 macro_rules! partial_eq_string {
@@ -14,6 +16,31 @@ macro_rules! partial_eq_string {
         partial_eq_string!(@imp, &str, $id);
         partial_eq_string!(@imp, String, $id);
     };
+    // Comparisons with references to the identifier. `&$id` vs `&str` is covered by the blanket
+    // implementation for references in std.
+    (@ref, $id:ty) => {
+        partial_eq_string!(@imp, &$id, str);
+        partial_eq_string!(@imp, &$id, String);
+        partial_eq_string!(@imp, str, &$id);
+        partial_eq_string!(@imp, String, &$id);
+    };
+    // Comparisons with other owned string types.
+    (@owned, $id:ty) => {
+        partial_eq_string!(@imp, $id, ::std::boxed::Box<str>);
+        partial_eq_string!(@imp, ::std::boxed::Box<str>, $id);
+
+        impl ::std::cmp::PartialEq<::std::borrow::Cow<'_, str>> for $id {
+            fn eq(&self, other: &::std::borrow::Cow<'_, str>) -> bool {
+                self.as_str() == &**other
+            }
+        }
+
+        impl ::std::cmp::PartialEq<$id> for ::std::borrow::Cow<'_, str> {
+            fn eq(&self, other: &$id) -> bool {
+                &**self == other.as_str()
+            }
+        }
+    };
     (@imp, $l:ty, $r:ty) => {
         impl ::std::cmp::PartialEq<$r> for $l {
             fn eq(&self, other: &$r) -> bool {
@@ -29,6 +56,7 @@ macro_rules! common_impls {
         impl $id {
             doc_concat! {
                 #[doc = concat!("Creates a string slice from this `", stringify!($id), "`")]
+                #[inline]
                 pub fn as_str(&self) -> &str {
                     &self.full_id
                 }
@@ -36,24 +64,50 @@ macro_rules! common_impls {
 
             doc_concat! {
                 #[doc = concat!("Creates a byte slice from this `", stringify!($id), "`")]
+                #[inline]
                 pub fn as_bytes(&self) -> &[u8] {
                     self.full_id.as_bytes()
                 }
             }
+
+            doc_concat! {
+                #[doc = concat!(
+                    "Returns this `",
+                    stringify!($id),
+                    "` for logging, truncated to `max_chars` characters followed by `…` if it is ",
+                    "longer.\n\nThis never splits a multi-byte character."
+                )]
+                pub fn truncated(&self, max_chars: usize) -> ::std::borrow::Cow<'_, str> {
+                    crate::truncate_id(self.as_str(), max_chars)
+                }
+            }
         }
 
         impl ::std::convert::AsRef<str> for $id {
+            #[inline]
             fn as_ref(&self) -> &str {
                 self.as_str()
             }
         }
 
+        impl ::std::borrow::Borrow<str> for $id {
+            fn borrow(&self) -> &str {
+                self.as_str()
+            }
+        }
+
         impl ::std::convert::From<$id> for ::std::string::String {
             fn from(id: $id) -> Self {
                 id.full_id.into()
             }
         }
 
+        impl ::std::convert::From<$id> for ::std::boxed::Box<str> {
+            fn from(id: $id) -> Self {
+                id.full_id
+            }
+        }
+
         impl ::std::str::FromStr for $id {
             type Err = crate::Error;
 
@@ -78,6 +132,14 @@ macro_rules! common_impls {
             }
         }
 
+        impl ::std::convert::TryFrom<&[u8]> for $id {
+            type Error = crate::Error;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                $try_from(crate::str_from_utf8(bytes)?)
+            }
+        }
+
         impl ::std::fmt::Display for $id {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 write!(f, "{}", self.as_str())
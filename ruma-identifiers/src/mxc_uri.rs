@@ -0,0 +1,245 @@
+//! Matrix content (`mxc://`) URIs.
+
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    fmt::{self, Display},
+    mem,
+    str::FromStr,
+};
+
+use ruma_identifiers_validation::mxc_uri::validate;
+
+use crate::{Error, ServerName};
+
+/// A URI that should be a Matrix-spec compliant MXC URI, i.e. `mxc://<server-name>/<media-id>`.
+#[repr(transparent)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde1::Serialize), serde(transparent, crate = "serde1"))]
+pub struct MxcUri(str);
+
+/// An owned MXC URI.
+pub type MxcUriBox = Box<MxcUri>;
+
+impl MxcUri {
+    #[allow(clippy::transmute_ptr_to_ptr)]
+    fn from_borrowed(s: &str) -> &Self {
+        unsafe { mem::transmute(s) }
+    }
+
+    fn from_owned(s: Box<str>) -> Box<Self> {
+        unsafe { mem::transmute(s) }
+    }
+
+    fn into_owned(self: Box<Self>) -> Box<str> {
+        unsafe { mem::transmute(self) }
+    }
+
+    /// Creates a string slice from this `MxcUri`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Creates a byte slice from this `MxcUri`.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns this `MxcUri` for logging, truncated to `max_chars` characters followed by `…`
+    /// if it is longer.
+    ///
+    /// This never splits a multi-byte character.
+    pub fn truncated(&self, max_chars: usize) -> Cow<'_, str> {
+        crate::truncate_id(self.as_str(), max_chars)
+    }
+
+    /// Returns the server name of the MXC URI.
+    pub fn server_name(&self) -> &ServerName {
+        <&ServerName>::try_from(&self.0["mxc://".len()..self.slash_idx()])
+            .expect("MXC URIs always contain a valid server name")
+    }
+
+    /// Returns the media ID of the MXC URI.
+    pub fn media_id(&self) -> &str {
+        &self.0[self.slash_idx() + 1..]
+    }
+
+    fn slash_idx(&self) -> usize {
+        "mxc://".len() + self.0["mxc://".len()..].find('/').unwrap()
+    }
+}
+
+impl Clone for Box<MxcUri> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl ToOwned for MxcUri {
+    type Owned = Box<MxcUri>;
+
+    fn to_owned(&self) -> Self::Owned {
+        Self::from_owned(self.0.to_owned().into_boxed_str())
+    }
+}
+
+impl From<&MxcUri> for Box<MxcUri> {
+    fn from(uri: &MxcUri) -> Self {
+        uri.to_owned()
+    }
+}
+
+fn try_from<S>(uri: S) -> Result<Box<MxcUri>, Error>
+where
+    S: AsRef<str> + Into<Box<str>>,
+{
+    validate(uri.as_ref())?;
+    Ok(MxcUri::from_owned(uri.into()))
+}
+
+impl AsRef<str> for MxcUri {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Box<MxcUri> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<Box<MxcUri>> for String {
+    fn from(uri: Box<MxcUri>) -> Self {
+        uri.into_owned().into()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for &'a MxcUri {
+    type Error = Error;
+
+    fn try_from(uri: &'a str) -> Result<Self, Self::Error> {
+        validate(uri)?;
+        Ok(MxcUri::from_borrowed(uri))
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for &'a MxcUri {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from(crate::str_from_utf8(bytes)?)
+    }
+}
+
+impl FromStr for Box<MxcUri> {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        try_from(s)
+    }
+}
+
+impl TryFrom<&str> for Box<MxcUri> {
+    type Error = crate::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        try_from(s)
+    }
+}
+
+impl TryFrom<String> for Box<MxcUri> {
+    type Error = crate::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        try_from(s)
+    }
+}
+
+impl TryFrom<&[u8]> for Box<MxcUri> {
+    type Error = crate::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        try_from(crate::str_from_utf8(bytes)?)
+    }
+}
+
+impl Display for MxcUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "serde1")]
+impl<'de> serde1::Deserialize<'de> for Box<MxcUri> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde1::Deserializer<'de>,
+    {
+        crate::deserialize_id(deserializer, "a Matrix content (mxc://) URI")
+    }
+}
+
+partial_eq_string!(MxcUri);
+partial_eq_string!(Box<MxcUri>);
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    #[cfg(feature = "serde")]
+    use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+    use super::MxcUri;
+    use crate::{Error, ServerNameError};
+
+    #[test]
+    fn valid_mxc_uri() {
+        let uri = <&MxcUri>::try_from("mxc://example.org/abcdef").unwrap();
+        assert_eq!(uri.server_name(), "example.org");
+        assert_eq!(uri.media_id(), "abcdef");
+    }
+
+    #[test]
+    fn valid_mxc_uri_with_port() {
+        let uri = Box::<MxcUri>::try_from("mxc://[::1]:8448/abcdef").unwrap();
+        assert_eq!(uri.server_name(), "[::1]:8448");
+        assert_eq!(uri.media_id(), "abcdef");
+        assert_eq!(uri.to_string(), "mxc://[::1]:8448/abcdef");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_mxc_uri() {
+        let uri: Box<MxcUri> = from_json_value(json!("mxc://example.org/abcdef")).unwrap();
+        assert_eq!(uri.media_id(), "abcdef");
+        assert_eq!(to_json_value(&uri).unwrap(), json!("mxc://example.org/abcdef"));
+    }
+
+    #[test]
+    fn missing_scheme() {
+        assert_eq!(
+            <&MxcUri>::try_from("https://example.org/abcdef").unwrap_err(),
+            Error::MissingMxcScheme
+        );
+        assert_eq!(<&MxcUri>::try_from("example.org/abcdef").unwrap_err(), Error::MissingMxcScheme);
+    }
+
+    #[test]
+    fn empty_media_id() {
+        assert_eq!(<&MxcUri>::try_from("mxc://example.org/").unwrap_err(), Error::EmptyMediaId);
+        assert_eq!(<&MxcUri>::try_from("mxc://example.org").unwrap_err(), Error::EmptyMediaId);
+    }
+
+    #[test]
+    fn malformed_server_name() {
+        assert_eq!(
+            <&MxcUri>::try_from("mxc://exa_mple.org/abcdef").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
+        assert_eq!(
+            <&MxcUri>::try_from("mxc:///abcdef").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::Empty)
+        );
+    }
+}
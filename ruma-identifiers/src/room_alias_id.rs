@@ -56,7 +56,7 @@ mod tests {
     use serde_json::{from_str, to_string};
 
     use super::RoomAliasId;
-    use crate::Error;
+    use crate::{Error, ServerNameError};
 
     #[test]
     fn valid_room_alias_id() {
@@ -150,14 +150,17 @@ mod tests {
 
     #[test]
     fn invalid_room_alias_id_host() {
-        assert_eq!(RoomAliasId::try_from("#ruma:/").unwrap_err(), Error::InvalidServerName);
+        assert_eq!(
+            RoomAliasId::try_from("#ruma:/").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
     }
 
     #[test]
     fn invalid_room_alias_id_port() {
         assert_eq!(
             RoomAliasId::try_from("#ruma:example.com:notaport").unwrap_err(),
-            Error::InvalidServerName
+            Error::InvalidServerName(ServerNameError::InvalidPort)
         );
     }
 }
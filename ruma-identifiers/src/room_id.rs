@@ -72,7 +72,7 @@ mod tests {
     use serde_json::{from_str, to_string};
 
     use super::RoomId;
-    use crate::Error;
+    use crate::{Error, ServerNameError};
 
     #[test]
     fn valid_room_id() {
@@ -160,14 +160,17 @@ mod tests {
 
     #[test]
     fn invalid_room_id_host() {
-        assert_eq!(RoomId::try_from("!29fhd83h92h0:/").unwrap_err(), Error::InvalidServerName);
+        assert_eq!(
+            RoomId::try_from("!29fhd83h92h0:/").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
     }
 
     #[test]
     fn invalid_room_id_port() {
         assert_eq!(
             RoomId::try_from("!29fhd83h92h0:example.com:notaport").unwrap_err(),
-            Error::InvalidServerName
+            Error::InvalidServerName(ServerNameError::InvalidPort)
         );
     }
 }
@@ -169,6 +169,42 @@ mod tests {
         assert_eq!(RoomIdOrAliasId::try_from("ruma:example.com").unwrap_err(), Error::MissingSigil);
     }
 
+    #[test]
+    fn invalid_sigil_for_room_id_or_alias_id() {
+        let err = "@ruma:example.com".parse::<RoomIdOrAliasId>().unwrap_err();
+        assert_eq!(err, Error::MissingSigil);
+        assert_eq!(err.to_string(), "leading sigil is incorrect or missing");
+    }
+
+    #[test]
+    fn room_id_or_alias_id_variant() {
+        let room_id = RoomIdOrAliasId::try_from("!29fhd83h92h0:example.com").unwrap();
+        assert!(room_id.is_room_id());
+        assert!(!room_id.is_room_alias_id());
+
+        let room_alias_id = RoomIdOrAliasId::try_from("#ruma:example.com").unwrap();
+        assert!(!room_alias_id.is_room_id());
+        assert!(room_alias_id.is_room_alias_id());
+    }
+
+    #[cfg(feature = "either")]
+    #[test]
+    fn room_id_or_alias_id_into_either() {
+        use either::Either;
+
+        let room_id = RoomIdOrAliasId::try_from("!29fhd83h92h0:example.com").unwrap();
+        match room_id.into_either() {
+            Either::Left(room_id) => assert_eq!(room_id.localpart(), "29fhd83h92h0"),
+            Either::Right(_) => panic!("expected a room ID"),
+        }
+
+        let room_alias_id = RoomIdOrAliasId::try_from("#ruma:example.com").unwrap();
+        match room_alias_id.into_either() {
+            Either::Left(_) => panic!("expected a room alias ID"),
+            Either::Right(room_alias_id) => assert_eq!(room_alias_id.alias(), "ruma"),
+        }
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serialize_valid_room_id_or_alias_id_with_a_room_alias_id() {
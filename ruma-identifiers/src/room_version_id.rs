@@ -7,7 +7,7 @@ use std::{
     str::FromStr,
 };
 
-#[cfg(feature = "serde")]
+#[cfg(feature = "serde1")]
 use serde1::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::Error;
@@ -69,6 +69,18 @@ impl RoomVersionId {
     pub fn as_bytes(&self) -> &[u8] {
         self.as_str().as_bytes()
     }
+
+    /// Whether event IDs in rooms of this version are in the format introduced in room version 3,
+    /// i.e. a hash of the event without a server name.
+    ///
+    /// See [`EventIdVersion::V3`](enum.EventIdVersion.html#variant.V3). This is `false` for
+    /// custom room versions, since their event ID format is unknown.
+    pub fn uses_event_id_format_v3(&self) -> bool {
+        match self {
+            Self::Version1 | Self::Version2 | Self::_Custom(_) => false,
+            Self::Version3 | Self::Version4 | Self::Version5 | Self::Version6 => true,
+        }
+    }
 }
 
 impl From<RoomVersionId> for String {
@@ -119,7 +131,7 @@ impl Ord for RoomVersionId {
     }
 }
 
-#[cfg(feature = "serde")]
+#[cfg(feature = "serde1")]
 impl Serialize for RoomVersionId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -129,7 +141,7 @@ impl Serialize for RoomVersionId {
     }
 }
 
-#[cfg(feature = "serde")]
+#[cfg(feature = "serde1")]
 impl<'de> Deserialize<'de> for RoomVersionId {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -184,6 +196,14 @@ impl TryFrom<String> for RoomVersionId {
     }
 }
 
+impl TryFrom<&[u8]> for RoomVersionId {
+    type Error = crate::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        try_from(crate::str_from_utf8(bytes)?)
+    }
+}
+
 impl PartialEq<&str> for RoomVersionId {
     fn eq(&self, other: &&str) -> bool {
         self.as_str() == *other
@@ -300,6 +320,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn room_version_id_round_trip() {
+        for version in &["1", "2", "3", "4", "5", "6", "io.ruma.1"] {
+            assert_eq!(version.parse::<RoomVersionId>().unwrap().to_string(), *version);
+        }
+    }
+
+    #[test]
+    fn event_id_format() {
+        assert!(!RoomVersionId::Version1.uses_event_id_format_v3());
+        assert!(!RoomVersionId::Version2.uses_event_id_format_v3());
+        assert!(RoomVersionId::Version3.uses_event_id_format_v3());
+        assert!(RoomVersionId::Version6.uses_event_id_format_v3());
+        assert!(!RoomVersionId::try_from("io.ruma.1").unwrap().uses_event_id_format_v3());
+    }
+
     #[test]
     fn empty_room_version_id() {
         assert_eq!(RoomVersionId::try_from(""), Err(Error::EmptyRoomVersionId));
@@ -1,42 +1,243 @@
 //! Identifiers for homeserver signing keys used for federation.
 
-use std::{convert::TryInto, num::NonZeroU8, str::FromStr};
+use std::{cmp::Ordering, convert::TryFrom, num::NonZeroU8, ops::Deref};
 
-use ruma_identifiers_validation::{crypto_algorithms::ServerKeyAlgorithm, Error};
+use ruma_identifiers_validation::{
+    crypto_algorithms::{DeviceKeyAlgorithm, ServerKeyAlgorithm},
+    Error,
+};
+
+use crate::{cmp_key_id_parts, key_id_from_parts, key_id_with_identifier, DeviceKeyId};
 
 /// Key identifiers used for homeserver signing keys.
+///
+/// Cloning a `ServerKeyId` copies the key ID into a new allocation. If key IDs are cloned a lot,
+/// consider sharing them through an `Arc<ServerKeyId>` instead.
 #[derive(Clone, Debug)]
+// Invariant: `full_id` is a valid server key ID and `colon_idx` is the index of its first colon.
+// Every safe constructor validates the version, which `From<&ServerKeyId> for DeviceKeyId` relies
+// on to skip validation.
 pub struct ServerKeyId {
     full_id: Box<str>,
     colon_idx: NonZeroU8,
 }
 
 impl ServerKeyId {
-    /// Create a `ServerKeyId` from a `ServerKeyAlgorithm` and a `ServerId`.
+    /// Create a `ServerKeyId` from a `ServerKeyAlgorithm` and a version.
+    ///
+    /// Panics if the algorithm name is empty, longer than 255 bytes or contains a colon, or if the
+    /// version isn't valid. Use [`try_from_parts`](#method.try_from_parts) for a non-panicking
+    /// version.
     pub fn from_parts(algorithm: ServerKeyAlgorithm, version: &str) -> Self {
-        let algorithm: &str = algorithm.as_ref();
+        Self::try_from_parts(algorithm, version).expect("valid algorithm name and version")
+    }
+
+    /// Checks whether the given string is a valid `ServerKeyId`, without creating one.
+    ///
+    /// This doesn't allocate, unlike `ServerKeyId::try_from`.
+    pub fn validate_only(s: &str) -> Result<(), Error> {
+        ruma_identifiers_validation::server_key_id::validate(s).map(|_| ())
+    }
+
+    /// Creates a `ServerKeyId` from bytes that haven't been checked to be valid UTF-8 yet.
+    ///
+    /// `ServerKeyId::try_from(&[u8])` checks the whole input for valid UTF-8 before validating
+    /// it, which means scanning the version twice. Since versions may only contain the ASCII
+    /// characters `[a-zA-Z0-9_]`, this checks them in a single pass instead. Inputs that are
+    /// invalid in more than one way may result in a different error, e.g. a version that isn't
+    /// valid UTF-8 results in `Error::InvalidKeyVersion` rather than `Error::InvalidUtf8`.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (key_id, colon_idx) =
+            ruma_identifiers_validation::server_key_id::validate_bytes(bytes)?;
+        Ok(Self { full_id: key_id.into(), colon_idx })
+    }
+
+    /// Creates a builder for a `ServerKeyId`, for setting the algorithm and the version
+    /// separately.
+    ///
+    /// ```
+    /// # use ruma_identifiers::{ServerKeyAlgorithm, ServerKeyId};
+    /// let key_id = ServerKeyId::builder()
+    ///     .algorithm(ServerKeyAlgorithm::Ed25519)
+    ///     .version("Abc_1")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(key_id, "ed25519:Abc_1");
+    /// ```
+    pub fn builder() -> ServerKeyIdBuilder {
+        ServerKeyIdBuilder::default()
+    }
 
-        let mut res = String::with_capacity(algorithm.len() + 1 + version.len());
-        res.push_str(algorithm);
-        res.push(':');
-        res.push_str(version);
+    /// Create a `ServerKeyId` from a `ServerKeyAlgorithm` and a version.
+    ///
+    /// Fails if the algorithm name is empty, longer than 255 bytes or contains a colon. Only the
+    /// part of a key ID before its first colon is its algorithm, so an algorithm with a colon, like
+    /// a custom one created from `"io.ruma:test"`, can't be part of a key ID.
+    ///
+    /// Fails with `Error::InvalidKeyVersion` if the version is empty or contains characters other
+    /// than `[a-zA-Z0-9_]`, like `ServerKeyId::try_from` does.
+    pub fn try_from_parts(algorithm: ServerKeyAlgorithm, version: &str) -> Result<Self, Error> {
+        ruma_identifiers_validation::server_key_id::validate_version(version)?;
+        let (full_id, colon_idx) = key_id_from_parts(algorithm.as_ref(), version)?;
+        Ok(ServerKeyId { full_id, colon_idx })
+    }
 
-        let colon_idx =
-            NonZeroU8::new(algorithm.len().try_into().expect("no algorithm name len > 255"))
-                .expect("no empty algorithm name");
+    /// Creates a `ServerKeyId` from a string that is already known to be a valid server key ID,
+    /// without checking it again.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU8;
+    /// # use ruma_identifiers::ServerKeyId;
+    /// let colon_idx = NonZeroU8::new(7).unwrap();
+    /// let key_id = unsafe { ServerKeyId::from_str_unchecked("ed25519:Abc_1", colon_idx) };
+    /// assert_eq!(key_id.as_algorithm_str(), "ed25519");
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `s` must be a valid server key ID and `colon_idx` must be the index of its first colon,
+    /// i.e. `colon_idx` must be what `ServerKeyId::try_from(s)` would compute. Other methods of
+    /// the returned `ServerKeyId` rely on this and may misbehave otherwise. This is only checked
+    /// in builds with debug assertions enabled.
+    pub unsafe fn from_str_unchecked(s: &str, colon_idx: NonZeroU8) -> Self {
+        debug_assert_eq!(
+            ruma_identifiers_validation::server_key_id::validate(s),
+            Ok(colon_idx),
+            "invalid server key ID passed to `ServerKeyId::from_str_unchecked`"
+        );
 
-        ServerKeyId { full_id: res.into(), colon_idx }
+        ServerKeyId { full_id: s.into(), colon_idx }
     }
 
     /// Returns key algorithm of the server key ID.
+    ///
+    /// This never panics: algorithm names that aren't known to ruma are returned as a custom
+    /// `ServerKeyAlgorithm` holding the original string.
     pub fn algorithm(&self) -> ServerKeyAlgorithm {
-        ServerKeyAlgorithm::from_str(&self.full_id[..self.colon_idx.get() as usize]).unwrap()
+        self.as_algorithm_str().into()
+    }
+
+    /// Returns the key algorithm of the server key ID as a string slice, without parsing it.
+    pub fn as_algorithm_str(&self) -> &str {
+        &self.full_id[..self.colon_idx.get() as usize]
+    }
+
+    /// Whether this key ID uses the given algorithm.
+    ///
+    /// This compares the algorithm's string representation with the raw algorithm of the key ID,
+    /// so the key ID's algorithm doesn't have to be parsed.
+    pub fn matches_algorithm(&self, algorithm: &ServerKeyAlgorithm) -> bool {
+        self.algorithm_str_eq(algorithm.as_str())
+    }
+
+    /// Whether the algorithm of this key ID is exactly the given string.
+    ///
+    /// Like [`matches_algorithm`](#method.matches_algorithm), but for algorithm names that are
+    /// only available as strings, e.g. because they come from a configuration file.
+    pub fn algorithm_str_eq(&self, algorithm: &str) -> bool {
+        self.as_algorithm_str() == algorithm
     }
 
     /// Returns the version of the server key ID.
     pub fn version(&self) -> &str {
         &self.full_id[self.colon_idx.get() as usize + 1..]
     }
+
+    /// Creates a new `ServerKeyId` with the same algorithm as this one, but the given version.
+    ///
+    /// This is useful when rotating keys, e.g. to get `ed25519:2` from `ed25519:1`. Fails with
+    /// `Error::InvalidKeyVersion` if the version is empty or contains characters other than
+    /// `[a-zA-Z0-9_]`.
+    pub fn with_version(&self, version: &str) -> Result<Self, Error> {
+        ruma_identifiers_validation::server_key_id::validate_version(version)?;
+        let full_id = key_id_with_identifier(&self.full_id, self.colon_idx, version);
+        Ok(ServerKeyId { full_id, colon_idx: self.colon_idx })
+    }
+
+    /// Compares this `ServerKeyId` to another one by algorithm, then by version.
+    ///
+    /// Unlike the `Ord` implementation, which compares the raw strings, versions are compared
+    /// numerically if both of them are integers, so `ed25519:2` sorts before `ed25519:10`. This
+    /// is meant for displaying lists of keys.
+    pub fn cmp_by_parts(&self, other: &Self) -> Ordering {
+        cmp_key_id_parts(
+            (self.as_algorithm_str(), self.version()),
+            (other.as_algorithm_str(), other.version()),
+        )
+    }
+
+    /// Returns the key algorithm and the version of the server key ID as string slices, without
+    /// parsing them.
+    pub fn as_parts(&self) -> (&str, &str) {
+        let colon_idx = self.colon_idx.get() as usize;
+        (&self.full_id[..colon_idx], &self.full_id[colon_idx + 1..])
+    }
+
+    /// Like [`as_parts`](#method.as_parts), but additionally returns the byte index of the colon
+    /// separating the key algorithm and the version.
+    ///
+    /// This is meant for feeding the parts of the key ID into a hasher without parsing them.
+    pub fn as_parts_with_colon_idx(&self) -> (&str, &str, usize) {
+        let (algorithm, version) = self.as_parts();
+        (algorithm, version, self.colon_idx.get() as usize)
+    }
+
+    /// Returns both the key algorithm and the version of the server key ID.
+    pub fn parts(&self) -> (ServerKeyAlgorithm, &str) {
+        (self.algorithm(), self.version())
+    }
+
+    /// Reinterprets this `ServerKeyId` as a `DeviceKeyId`, using its version as the device ID.
+    ///
+    /// Unlike the `From<&ServerKeyId>` implementation for `DeviceKeyId`, this fails with
+    /// `Error::UnknownKeyAlgorithm` unless the algorithm is known to ruma both as a
+    /// `ServerKeyAlgorithm` and as a `DeviceKeyAlgorithm`, like `ed25519`.
+    pub fn to_device_key_id(&self) -> Result<DeviceKeyId, Error> {
+        let algorithm = self.as_algorithm_str();
+        match (ServerKeyAlgorithm::from(algorithm), DeviceKeyAlgorithm::from(algorithm)) {
+            (ServerKeyAlgorithm::_Custom(_), _) | (_, DeviceKeyAlgorithm::_Custom(_)) => {
+                Err(Error::UnknownKeyAlgorithm)
+            }
+            _ => Ok(self.into()),
+        }
+    }
+}
+
+/// A builder for a `ServerKeyId`, created with [`ServerKeyId::builder`].
+///
+/// [`ServerKeyId::builder`]: struct.ServerKeyId.html#method.builder
+#[derive(Clone, Debug, Default)]
+pub struct ServerKeyIdBuilder {
+    algorithm: Option<ServerKeyAlgorithm>,
+    version: Option<String>,
+}
+
+impl ServerKeyIdBuilder {
+    /// Sets the key algorithm.
+    pub fn algorithm(mut self, algorithm: ServerKeyAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets the version.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Creates the `ServerKeyId`.
+    ///
+    /// Fails with `Error::EmptyKeyAlgorithm` if no algorithm was set and with
+    /// `Error::InvalidKeyVersion` if no version was set or the version isn't valid. Fails like
+    /// [`ServerKeyId::try_from_parts`] otherwise.
+    ///
+    /// [`ServerKeyId::try_from_parts`]: struct.ServerKeyId.html#method.try_from_parts
+    pub fn build(self) -> Result<ServerKeyId, Error> {
+        let algorithm = self.algorithm.ok_or(Error::EmptyKeyAlgorithm)?;
+        let version = self.version.ok_or(Error::InvalidKeyVersion)?;
+
+        ServerKeyId::try_from_parts(algorithm, &version)
+    }
 }
 
 fn try_from<S>(key_id: S) -> Result<ServerKeyId, Error>
@@ -48,17 +249,95 @@ where
 }
 
 common_impls!(ServerKeyId, try_from, "Key ID with algorithm and version");
+partial_eq_string!(@ref, ServerKeyId);
+partial_eq_string!(@owned, ServerKeyId);
+
+impl Deref for ServerKeyId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for ServerKeyId {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Like [`ServerKeyId::try_from_bytes`](struct.ServerKeyId.html#method.try_from_bytes), but reuses
+/// the allocation of the given bytes.
+impl TryFrom<Vec<u8>> for ServerKeyId {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Error> {
+        let (_, colon_idx) = ruma_identifiers_validation::server_key_id::validate_bytes(&bytes)?;
+        // Safety: `validate_bytes` only succeeds for valid UTF-8.
+        let full_id = unsafe { String::from_utf8_unchecked(bytes) }.into_boxed_str();
+        Ok(Self { full_id, colon_idx })
+    }
+}
+
+/// Creates a `ServerKeyId` from an `(algorithm, version)` pair, like
+/// [`ServerKeyId::try_from_parts`](struct.ServerKeyId.html#method.try_from_parts).
+impl TryFrom<(ServerKeyAlgorithm, &str)> for ServerKeyId {
+    type Error = Error;
+
+    fn try_from((algorithm, version): (ServerKeyAlgorithm, &str)) -> Result<Self, Error> {
+        Self::try_from_parts(algorithm, version)
+    }
+}
+
+/// Reinterprets a `ServerKeyId` as a `DeviceKeyId`, using its version as the device ID.
+///
+/// This can't fail, since every valid server key ID is also a valid device key ID.
+impl From<&ServerKeyId> for DeviceKeyId {
+    fn from(key_id: &ServerKeyId) -> Self {
+        // Safety: All safe constructors of `ServerKeyId` validate its version, so it is a
+        // non-empty string, which is always a valid device ID. The colon index doesn't change.
+        unsafe { DeviceKeyId::from_str_unchecked(key_id.as_str(), key_id.colon_idx) }
+    }
+}
+
+/// Reinterprets a `DeviceKeyId` as a `ServerKeyId`, using its device ID as the version.
+///
+/// Fails with `Error::InvalidKeyVersion` if the device ID isn't a valid key version, i.e. if it
+/// contains characters outside of `[a-zA-Z0-9_]`.
+impl TryFrom<&DeviceKeyId> for ServerKeyId {
+    type Error = Error;
+
+    fn try_from(key_id: &DeviceKeyId) -> Result<Self, Error> {
+        let (_, device_id, colon_idx) = key_id.as_parts_with_colon_idx();
+        ruma_identifiers_validation::server_key_id::validate_version(device_id)?;
+
+        // The algorithm of a `DeviceKeyId` is known to be between 1 and 255 bytes long.
+        let colon_idx = NonZeroU8::new(colon_idx as u8).expect("algorithm is not empty");
+
+        Ok(ServerKeyId { full_id: key_id.as_str().into(), colon_idx })
+    }
+}
+
+// Key IDs of both kinds are compared by their string representation.
+partial_eq_string!(@imp, ServerKeyId, DeviceKeyId);
+partial_eq_string!(@imp, DeviceKeyId, ServerKeyId);
 
 #[cfg(test)]
 mod tests {
-    use std::convert::TryFrom;
+    use std::{
+        borrow::Cow,
+        collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+        convert::TryFrom,
+        hash::{Hash, Hasher},
+        num::NonZeroU8,
+    };
 
     #[cfg(feature = "serde")]
     use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
 
-    use crate::{Error, ServerKeyId};
+    use crate::{DeviceKeyId, Error, ServerKeyId};
 
-    #[cfg(feature = "serde")]
     use ruma_identifiers_validation::crypto_algorithms::ServerKeyAlgorithm;
 
     #[cfg(feature = "serde")]
@@ -76,24 +355,421 @@ mod tests {
         assert_eq!(to_json_value(&server_key_id).unwrap(), json!("ed25519:abc123"));
     }
 
+    #[test]
+    fn server_key_id_from_parts_with_colon_in_algorithm() {
+        let algorithm = ServerKeyAlgorithm::from("io.ruma:test");
+        assert_eq!(
+            ServerKeyId::try_from_parts(algorithm, "1").unwrap_err(),
+            Error::InvalidKeyAlgorithm
+        );
+    }
+
+    #[test]
+    #[should_panic = "valid algorithm name"]
+    fn server_key_id_from_parts_with_colon_in_algorithm_panics() {
+        ServerKeyId::from_parts(ServerKeyAlgorithm::from("io.ruma:test"), "1");
+    }
+
+    #[test]
+    fn server_key_id_from_parts() {
+        let server_key_id = ServerKeyId::try_from_parts(ServerKeyAlgorithm::Ed25519, "1").unwrap();
+        assert_eq!(server_key_id, ServerKeyId::try_from("ed25519:1").unwrap());
+    }
+
+    #[test]
+    fn server_key_id_from_parts_with_invalid_version() {
+        for version in &["", "a-b", "a:b", "äöü"] {
+            assert_eq!(
+                ServerKeyId::try_from_parts(ServerKeyAlgorithm::Ed25519, version).unwrap_err(),
+                Error::InvalidKeyVersion,
+                "{}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn server_key_id_to_device_key_id() {
+        let server_key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let device_key_id = DeviceKeyId::from(&server_key_id);
+
+        assert_eq!(device_key_id, "ed25519:Abc_1");
+        assert_eq!(device_key_id.device_id(), "Abc_1");
+        assert_eq!(ServerKeyId::try_from(&device_key_id).unwrap(), server_key_id);
+    }
+
+    #[test]
+    fn constructed_server_key_ids_to_device_key_ids() {
+        let key_id = ServerKeyId::try_from("ed25519:1").unwrap();
+        let key_ids = [
+            ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "Abc_1"),
+            ServerKeyId::builder()
+                .algorithm(ServerKeyAlgorithm::Ed25519)
+                .version("Abc_1")
+                .build()
+                .unwrap(),
+            key_id.with_version("Abc_1").unwrap(),
+            ServerKeyId::try_from_bytes(b"ed25519:Abc_1").unwrap(),
+            ServerKeyId::try_from(b"ed25519:Abc_1".to_vec()).unwrap(),
+        ];
+
+        for key_id in key_ids.iter() {
+            assert_eq!(DeviceKeyId::from(key_id), DeviceKeyId::try_from(key_id.as_str()).unwrap());
+        }
+    }
+
+    #[test]
+    fn device_key_id_to_server_key_id() {
+        let device_key_id = DeviceKeyId::try_from("curve25519:JLAFKJWSCS").unwrap();
+        let server_key_id = ServerKeyId::try_from(&device_key_id).unwrap();
+        assert_eq!(server_key_id.as_parts(), ("curve25519", "JLAFKJWSCS"));
+
+        let device_key_id = DeviceKeyId::try_from("ed25519:dev:1").unwrap();
+        assert_eq!(ServerKeyId::try_from(&device_key_id).unwrap_err(), Error::InvalidKeyVersion);
+    }
+
+    #[test]
+    fn parse_server_key_id() {
+        let server_key_id = "ed25519:auto".parse::<ServerKeyId>().unwrap();
+        assert_eq!(server_key_id.algorithm(), ServerKeyAlgorithm::Ed25519);
+        assert_eq!(server_key_id.version(), "auto");
+    }
+
+    #[test]
+    fn server_key_id_from_tuple() {
+        let server_key_id = ServerKeyId::try_from((ServerKeyAlgorithm::Ed25519, "1")).unwrap();
+        assert_eq!(server_key_id, "ed25519:1");
+
+        assert_eq!(
+            ServerKeyId::try_from((ServerKeyAlgorithm::from(""), "1")).unwrap_err(),
+            Error::EmptyKeyAlgorithm
+        );
+    }
+
+    #[test]
+    fn server_key_id_from_str_unchecked() {
+        let colon_idx = NonZeroU8::new(7).unwrap();
+        let key_id = unsafe { ServerKeyId::from_str_unchecked("ed25519:Abc_1", colon_idx) };
+        assert_eq!(key_id, ServerKeyId::try_from("ed25519:Abc_1").unwrap());
+        assert_eq!(key_id.version(), "Abc_1");
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn server_key_id_from_str_unchecked_with_wrong_colon_idx() {
+        let colon_idx = NonZeroU8::new(3).unwrap();
+        let _ = unsafe { ServerKeyId::from_str_unchecked("ed25519:Abc_1", colon_idx) };
+    }
+
+    #[test]
+    fn rotate_server_key_id() {
+        let key_id = ServerKeyId::try_from("ed25519:1").unwrap();
+        let rotated_key_id = key_id.with_version("2").unwrap();
+
+        assert_eq!(rotated_key_id, "ed25519:2");
+        assert_eq!(rotated_key_id.algorithm(), key_id.algorithm());
+        assert_eq!(rotated_key_id.version(), "2");
+        assert_eq!(rotated_key_id, ServerKeyId::try_from("ed25519:2").unwrap());
+    }
+
+    #[test]
+    fn rotate_server_key_id_with_invalid_version() {
+        let key_id = ServerKeyId::try_from("ed25519:1").unwrap();
+        for version in &["", "a:b", "a-b", "äöü"] {
+            assert_eq!(key_id.with_version(version).unwrap_err(), Error::InvalidKeyVersion);
+        }
+    }
+
+    #[test]
+    fn sort_server_key_ids_by_parts() {
+        let mut key_ids: Vec<_> = ["ed25519:10", "ed25519:2", "ed25519:a_1", "abc:3", "ed25519:02"]
+            .iter()
+            .map(|&key_id| ServerKeyId::try_from(key_id).unwrap())
+            .collect();
+
+        key_ids.sort();
+        assert_eq!(key_ids, ["abc:3", "ed25519:02", "ed25519:10", "ed25519:2", "ed25519:a_1"]);
+
+        key_ids.sort_by(ServerKeyId::cmp_by_parts);
+        assert_eq!(key_ids, ["abc:3", "ed25519:02", "ed25519:2", "ed25519:10", "ed25519:a_1"]);
+    }
+
+    #[test]
+    fn matches_algorithm() {
+        let key_ids = ["ed25519:1", "io.ruma.test:1", "ed25519:2"];
+        let ed25519_key_ids: Vec<_> = key_ids
+            .iter()
+            .map(|s| ServerKeyId::try_from(*s).unwrap())
+            .filter(|key_id| key_id.matches_algorithm(&ServerKeyAlgorithm::Ed25519))
+            .collect();
+        assert_eq!(ed25519_key_ids, ["ed25519:1", "ed25519:2"]);
+
+        let custom_key_id = ServerKeyId::try_from("io.ruma.test:1").unwrap();
+        assert!(custom_key_id.matches_algorithm(&"io.ruma.test".into()));
+        assert!(!custom_key_id.matches_algorithm(&ServerKeyAlgorithm::Ed25519));
+    }
+
+    #[test]
+    fn build_server_key_id() {
+        let key_id = ServerKeyId::builder()
+            .version("Abc_1")
+            .algorithm(ServerKeyAlgorithm::Ed25519)
+            .build()
+            .unwrap();
+        assert_eq!(key_id, "ed25519:Abc_1");
+    }
+
+    #[test]
+    fn build_server_key_id_with_missing_or_invalid_parts() {
+        let missing_version = ServerKeyId::builder().algorithm(ServerKeyAlgorithm::Ed25519);
+        assert_eq!(missing_version.clone().build().unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(missing_version.version("Abc-1").build().unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(
+            ServerKeyId::builder().version("Abc_1").build().unwrap_err(),
+            Error::EmptyKeyAlgorithm
+        );
+    }
+
+    #[test]
+    fn validate_only() {
+        assert_eq!(ServerKeyId::validate_only("ed25519:Abc_1"), Ok(()));
+        assert_eq!(ServerKeyId::validate_only("ed25519:Abc-1"), Err(Error::InvalidKeyVersion));
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let key_id = ServerKeyId::try_from_bytes(b"ed25519:Abc_1").unwrap();
+        assert_eq!(key_id.as_parts(), ("ed25519", "Abc_1"));
+        assert_eq!(ServerKeyId::try_from(b"ed25519:Abc_1".to_vec()).unwrap(), key_id);
+
+        assert_eq!(ServerKeyId::try_from_bytes(b"ed\xff25519:1").unwrap_err(), Error::InvalidUtf8);
+        assert_eq!(
+            ServerKeyId::try_from_bytes(b"ed25519:\xff").unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+        assert_eq!(
+            ServerKeyId::try_from(b"ed25519:Abc-1".to_vec()).unwrap_err(),
+            Error::InvalidKeyVersion
+        );
+    }
+
+    #[test]
+    fn algorithm_str_eq() {
+        assert!(ServerKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ed25519"));
+        assert!(!ServerKeyId::try_from("curve25519:1").unwrap().algorithm_str_eq("ed25519"));
+        assert!(!ServerKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ED25519"));
+        assert!(!ServerKeyId::try_from("ed25519:1").unwrap().algorithm_str_eq("ed25519:1"));
+    }
+
+    #[test]
+    fn server_key_id_as_parts() {
+        let server_key_id = ServerKeyId::try_from("ed25519:abc").unwrap();
+        assert_eq!(server_key_id.as_parts(), ("ed25519", "abc"));
+        assert_eq!(server_key_id.as_parts_with_colon_idx(), ("ed25519", "abc", 7));
+    }
+
+    #[test]
+    fn server_key_id_parts() {
+        let server_key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        assert_eq!(server_key_id.as_algorithm_str(), "ed25519");
+        assert_eq!(server_key_id.parts(), (ServerKeyAlgorithm::Ed25519, "Abc_1"));
+    }
+
+    #[test]
+    fn compare_with_strings() {
+        let key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let key_ref = &key_id;
+        let string = String::from("ed25519:Abc_1");
+
+        assert!(key_id == *"ed25519:Abc_1");
+        assert!(key_id == "ed25519:Abc_1");
+        assert!(key_id == string);
+        assert!(*"ed25519:Abc_1" == key_id);
+        assert!("ed25519:Abc_1" == key_id);
+        assert!(string == key_id);
+
+        assert!(key_ref == *"ed25519:Abc_1");
+        assert!(key_ref == "ed25519:Abc_1");
+        assert!(key_ref == string);
+        assert!(*"ed25519:Abc_1" == key_ref);
+        assert!("ed25519:Abc_1" == key_ref);
+        assert!(string == key_ref);
+
+        let other_string = String::from("ed25519:Abc_2");
+        assert!(key_ref != "ed25519:Abc_2");
+        assert!(other_string != key_ref);
+    }
+
+    #[test]
+    fn compare_with_device_key_id() {
+        let server_key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let device_key_id = DeviceKeyId::try_from("ed25519:Abc_1").unwrap();
+        let other_device_key_id = DeviceKeyId::try_from("ed25519:Abc_2").unwrap();
+
+        assert_eq!(server_key_id, device_key_id);
+        assert_eq!(device_key_id, server_key_id);
+        assert_ne!(server_key_id, other_device_key_id);
+    }
+
+    #[test]
+    fn into_boxed_str() {
+        let key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let boxed: Box<str> = key_id.clone().into();
+
+        assert_eq!(&*boxed, "ed25519:Abc_1");
+        assert_eq!(ServerKeyId::try_from(&*boxed).unwrap(), key_id);
+    }
+
+    #[test]
+    fn deref_to_str() {
+        let key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        assert!(key_id.starts_with("ed25519:"));
+        assert_eq!(key_id.len(), 13);
+    }
+
+    #[test]
+    fn borrow_server_key_id_as_str() {
+        let key_id = ServerKeyId::try_from("ed25519:1").unwrap();
+
+        let mut btree_map = BTreeMap::new();
+        btree_map.insert(key_id.clone(), "sig");
+        assert_eq!(btree_map.get("ed25519:1"), Some(&"sig"));
+
+        let mut hash_map = HashMap::new();
+        hash_map.insert(key_id, "sig");
+        assert_eq!(hash_map.get("ed25519:1"), Some(&"sig"));
+        assert_eq!(hash_map.get("ed25519:2"), None);
+    }
+
+    #[test]
+    fn hash_matches_str_hash() {
+        fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        // Required for the `Borrow<str>` implementation to be used for lookups in hash maps.
+        for s in &["ed25519:1", "ed25519:Abc_1", "io.ruma.test:key_0", "äöü:1", "x:_"] {
+            let key_id = ServerKeyId::try_from(*s).unwrap();
+            assert_eq!(hash_of(&key_id), hash_of(*s), "{}", s);
+            assert_eq!(hash_of(&key_id), hash_of(key_id.as_str()), "{}", s);
+        }
+    }
+
+    #[test]
+    fn server_key_id_from_bytes() {
+        assert_eq!(ServerKeyId::try_from(&b"ed25519:Abc_1"[..]).unwrap(), "ed25519:Abc_1");
+        assert_eq!(ServerKeyId::try_from(&b"ed25519:\xff"[..]).unwrap_err(), Error::InvalidUtf8);
+        assert_eq!(
+            ServerKeyId::try_from(&b"ed25519|Abc_1"[..]).unwrap_err(),
+            Error::MissingServerKeyDelimiter { len: 13 }
+        );
+    }
+
     #[test]
     fn invalid_version_characters() {
-        assert_eq!(ServerKeyId::try_from("ed25519:Abc-1").unwrap_err(), Error::InvalidCharacters);
+        assert_eq!(ServerKeyId::try_from("ed25519:Abc-1").unwrap_err(), Error::InvalidKeyVersion);
     }
 
     #[test]
-    fn invalid_key_algorithm() {
+    fn non_ascii_version_characters() {
+        assert_eq!(ServerKeyId::try_from("ed25519:Äbc_1").unwrap_err(), Error::InvalidKeyVersion);
+        assert_eq!(ServerKeyId::try_from("ed25519:１").unwrap_err(), Error::InvalidKeyVersion);
+    }
+
+    #[test]
+    fn custom_key_algorithm() {
+        let server_key_id = ServerKeyId::try_from("io.ruma.test:Abc_1").unwrap();
+        assert_eq!(server_key_id.algorithm().as_str(), "io.ruma.test");
+        assert_eq!(server_key_id.version(), "Abc_1");
+    }
+
+    #[test]
+    fn valid_key_algorithm() {
         assert_eq!(
-            ServerKeyId::try_from("signed_curve25519:Abc-1").unwrap_err(),
-            Error::UnknownKeyAlgorithm,
+            ServerKeyId::try_from("ed25519:1").unwrap().algorithm(),
+            ServerKeyAlgorithm::Ed25519
         );
     }
 
+    #[test]
+    fn missing_key_algorithm() {
+        assert_eq!(ServerKeyId::try_from(":Abc_1").unwrap_err(), Error::EmptyKeyAlgorithm);
+    }
+
+    #[test]
+    fn key_algorithm_too_long() {
+        let key_id = format!("{}:Abc_1", "a".repeat(300));
+        assert_eq!(ServerKeyId::try_from(key_id).unwrap_err(), Error::AlgorithmNameTooLong);
+    }
+
+    #[test]
+    fn empty_version() {
+        assert_eq!(ServerKeyId::try_from("ed25519:").unwrap_err(), Error::InvalidKeyVersion);
+    }
+
     #[test]
     fn missing_delimiter() {
         assert_eq!(
             ServerKeyId::try_from("ed25519|Abc_1").unwrap_err(),
-            Error::MissingServerKeyDelimiter,
+            Error::MissingServerKeyDelimiter { len: 13 },
+        );
+    }
+
+    #[test]
+    fn missing_delimiter_reports_input_length() {
+        assert_eq!(
+            ServerKeyId::try_from("").unwrap_err(),
+            Error::MissingServerKeyDelimiter { len: 0 }
         );
+        assert_eq!(
+            ServerKeyId::try_from("ed25519").unwrap_err(),
+            Error::MissingServerKeyDelimiter { len: 7 }
+        );
+    }
+
+    #[test]
+    fn to_device_key_id_with_shared_algorithm() {
+        let key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let device_key_id = key_id.to_device_key_id().unwrap();
+        assert_eq!(device_key_id, "ed25519:Abc_1");
+        assert_eq!(device_key_id.device_id(), "Abc_1");
+    }
+
+    #[test]
+    fn parse_as_device_signing_key_id() {
+        fn parse(s: &str) -> Result<DeviceKeyId, Error> {
+            ServerKeyId::try_from(s).and_then(|key_id| key_id.to_device_key_id())
+        }
+
+        assert_eq!(parse("ed25519:DEVID").unwrap().device_id(), "DEVID");
+        assert_eq!(parse("ed25519:").unwrap_err(), Error::InvalidKeyVersion);
+    }
+
+    #[test]
+    fn to_device_key_id_with_server_only_algorithm() {
+        let key_id = ServerKeyId::try_from("io.ruma.signing_only:1").unwrap();
+        assert_eq!(key_id.to_device_key_id().unwrap_err(), Error::UnknownKeyAlgorithm);
+    }
+
+    #[test]
+    fn compare_with_owned_strings() {
+        let key_id = ServerKeyId::try_from("ed25519:Abc_1").unwrap();
+        let boxed: Box<str> = "ed25519:Abc_1".into();
+        let borrowed_cow = Cow::Borrowed("ed25519:Abc_1");
+        let owned_cow: Cow<'_, str> = Cow::Owned("ed25519:Abc_1".to_owned());
+
+        assert_eq!(key_id, boxed);
+        assert_eq!(boxed, key_id);
+        assert_eq!(key_id, borrowed_cow);
+        assert_eq!(borrowed_cow, key_id);
+        assert_eq!(key_id, owned_cow);
+        assert_eq!(owned_cow, key_id);
+
+        let other: Box<str> = "ed25519:Abc_2".into();
+        assert_ne!(key_id, other);
+        assert_ne!(Cow::Borrowed("ed25519:abc_1"), key_id);
     }
 }
@@ -1,9 +1,11 @@
 //! Matrix-spec compliant server names.
 
 use std::{
+    borrow::Cow,
     convert::TryFrom,
     fmt::{self, Display},
     mem,
+    net::IpAddr,
     str::FromStr,
 };
 
@@ -14,7 +16,7 @@ use crate::Error;
 /// A Matrix-spec compliant server name.
 #[repr(transparent)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde1::Serialize), serde(transparent, crate = "serde1"))]
+#[cfg_attr(feature = "serde1", derive(serde1::Serialize), serde(transparent, crate = "serde1"))]
 pub struct ServerName(str);
 
 /// An owned server name.
@@ -43,6 +45,65 @@ impl ServerName {
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_bytes()
     }
+
+    /// Returns this `ServerName` for logging, truncated to `max_chars` characters followed by `…`
+    /// if it is longer.
+    ///
+    /// This never splits a multi-byte character.
+    pub fn truncated(&self, max_chars: usize) -> Cow<'_, str> {
+        crate::truncate_id(self.as_str(), max_chars)
+    }
+
+    /// Returns the host of the server name, without the port.
+    ///
+    /// For IPv6 literals, the surrounding brackets are removed, e.g. the host of `[::1]:8448` is
+    /// `::1`.
+    pub fn host(&self) -> &str {
+        let end_of_host = self.end_of_host();
+
+        if self.0.starts_with('[') {
+            &self.0[1..end_of_host - 1]
+        } else {
+            &self.0[..end_of_host]
+        }
+    }
+
+    /// Returns the port of the server name, if any.
+    pub fn port(&self) -> Option<u16> {
+        self.0.get(self.end_of_host() + 1..).map(|port| port.parse().unwrap())
+    }
+
+    /// Whether the host of this server name is an IPv4 or IPv6 address.
+    pub fn is_ip_literal(&self) -> bool {
+        self.host().parse::<IpAddr>().is_ok()
+    }
+
+    /// Returns a copy of this server name with the host converted to ASCII lowercase.
+    ///
+    /// IP literals and the port are left unchanged. Domain names are case-insensitive, so this is
+    /// useful for comparing server names, e.g. as the key of a cache. It is meant for comparison
+    /// only: the original server name must be used wherever it is signed or sent to others.
+    pub fn to_ascii_lowercase_owned(&self) -> Box<ServerName> {
+        if self.is_ip_literal() {
+            return self.to_owned();
+        }
+
+        let end_of_host = self.end_of_host();
+        let mut lowercased = String::with_capacity(self.0.len());
+        lowercased.push_str(&self.0[..end_of_host].to_ascii_lowercase());
+        lowercased.push_str(&self.0[end_of_host..]);
+
+        debug_assert_eq!(validate(&lowercased), Ok(()));
+        Self::from_owned(lowercased.into_boxed_str())
+    }
+
+    fn end_of_host(&self) -> usize {
+        if self.0.starts_with('[') {
+            self.0.find(']').unwrap() + 1
+        } else {
+            self.0.find(':').unwrap_or(self.0.len())
+        }
+    }
 }
 
 impl Clone for Box<ServerName> {
@@ -100,6 +161,14 @@ impl<'a> TryFrom<&'a str> for &'a ServerName {
     }
 }
 
+impl<'a> TryFrom<&'a [u8]> for &'a ServerName {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from(crate::str_from_utf8(bytes)?)
+    }
+}
+
 impl FromStr for Box<ServerName> {
     type Err = crate::Error;
 
@@ -124,13 +193,21 @@ impl TryFrom<String> for Box<ServerName> {
     }
 }
 
+impl TryFrom<&[u8]> for Box<ServerName> {
+    type Error = crate::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        try_from(crate::str_from_utf8(bytes)?)
+    }
+}
+
 impl Display for ServerName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
-#[cfg(feature = "serde")]
+#[cfg(feature = "serde1")]
 impl<'de> serde1::Deserialize<'de> for Box<ServerName> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -148,6 +225,7 @@ mod tests {
     use std::convert::TryFrom;
 
     use super::ServerName;
+    use crate::{Error, ServerNameError};
 
     #[test]
     fn ipv4_host() {
@@ -179,6 +257,119 @@ mod tests {
         assert!(<&ServerName>::try_from("ruma.io:8080").is_ok());
     }
 
+    #[test]
+    fn dns_name_parts() {
+        let server_name = <&ServerName>::try_from("example.org").unwrap();
+        assert_eq!(server_name.host(), "example.org");
+        assert_eq!(server_name.port(), None);
+        assert!(!server_name.is_ip_literal());
+    }
+
+    #[test]
+    fn dns_name_with_port_parts() {
+        let server_name = <&ServerName>::try_from("example.org:8448").unwrap();
+        assert_eq!(server_name.host(), "example.org");
+        assert_eq!(server_name.port(), Some(8448));
+        assert!(!server_name.is_ip_literal());
+    }
+
+    #[test]
+    fn ipv4_parts() {
+        let server_name = <&ServerName>::try_from("127.0.0.1").unwrap();
+        assert_eq!(server_name.host(), "127.0.0.1");
+        assert_eq!(server_name.port(), None);
+        assert!(server_name.is_ip_literal());
+    }
+
+    #[test]
+    fn ipv6_parts() {
+        let server_name = <&ServerName>::try_from("[::1]").unwrap();
+        assert_eq!(server_name.host(), "::1");
+        assert_eq!(server_name.port(), None);
+        assert!(server_name.is_ip_literal());
+    }
+
+    #[test]
+    fn ipv6_with_port_parts() {
+        let server_name = <&ServerName>::try_from("[::1]:8448").unwrap();
+        assert_eq!(server_name.host(), "::1");
+        assert_eq!(server_name.port(), Some(8448));
+        assert!(server_name.is_ip_literal());
+    }
+
+    #[test]
+    fn lowercase_mixed_case_host() {
+        let server_name = <&ServerName>::try_from("Matrix.EXAMPLE.org:8448").unwrap();
+        let lowercased = server_name.to_ascii_lowercase_owned();
+
+        assert_eq!(lowercased.as_str(), "matrix.example.org:8448");
+        assert_eq!(lowercased.port(), Some(8448));
+        assert_eq!(server_name, "Matrix.EXAMPLE.org:8448");
+    }
+
+    #[test]
+    fn lowercase_ip_literal() {
+        let server_name = <&ServerName>::try_from("[::ABCD]:8448").unwrap();
+        assert_eq!(server_name.to_ascii_lowercase_owned().as_str(), "[::ABCD]:8448");
+
+        let server_name = <&ServerName>::try_from("127.0.0.1").unwrap();
+        assert_eq!(server_name.to_ascii_lowercase_owned().as_str(), "127.0.0.1");
+    }
+
+    #[test]
+    fn server_name_from_bytes() {
+        assert_eq!(<&ServerName>::try_from(&b"example.org"[..]).unwrap(), "example.org");
+        assert_eq!(
+            <&ServerName>::try_from(&b"\xffexample.org"[..]).unwrap_err(),
+            Error::InvalidUtf8
+        );
+        assert_eq!(
+            Box::<ServerName>::try_from(&b"example.org:hello"[..]).unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidPort)
+        );
+    }
+
+    #[test]
+    fn dns_name_with_uppercase_letters() {
+        assert_eq!(<&ServerName>::try_from("Example.ORG").unwrap(), "Example.ORG");
+    }
+
+    #[test]
+    fn dns_name_with_trailing_dot() {
+        assert_eq!(
+            <&ServerName>::try_from("example.org.").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::EmptyLabel)
+        );
+        assert_eq!(
+            <&ServerName>::try_from("example.org.:8448").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::EmptyLabel)
+        );
+    }
+
+    #[test]
+    fn dns_name_with_empty_label() {
+        assert_eq!(
+            <&ServerName>::try_from("example..org").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::EmptyLabel)
+        );
+        assert_eq!(
+            <&ServerName>::try_from(".example.org").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::EmptyLabel)
+        );
+    }
+
+    #[test]
+    fn dns_name_with_long_label() {
+        let max_label = "a".repeat(63);
+        assert!(<&ServerName>::try_from(format!("{}.example.org", max_label).as_str()).is_ok());
+
+        let long_label = "a".repeat(64);
+        assert_eq!(
+            <&ServerName>::try_from(format!("{}.example.org", long_label).as_str()).unwrap_err(),
+            Error::InvalidServerName(ServerNameError::LabelTooLong)
+        );
+    }
+
     #[test]
     fn empty_string() {
         assert!(<&ServerName>::try_from("").is_err());
@@ -0,0 +1,959 @@
+//! Maps of signatures keyed by the signing entity and key identifier.
+
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    iter::FromIterator,
+    mem,
+    ops::{Bound, Deref, DerefMut},
+};
+
+use crate::{
+    DeviceKeyAlgorithm, DeviceKeyId, Error, ServerKeyAlgorithm, ServerKeyId, ServerName,
+    ServerNameBox, UserId,
+};
+
+/// Map of key identifier to signature values.
+pub type EntitySignatures<K> = BTreeMap<K, String>;
+
+/// Map of all signatures, grouped by entity.
+///
+/// This dereferences to the underlying `BTreeMap<E, EntitySignatures<K>>`, so all of the regular
+/// map methods are available as well.
+///
+/// ```
+/// # use ruma_identifiers::{server_name, ServerKeyAlgorithm, ServerKeyId, ServerSignatures};
+/// let server_name = server_name!("example.org");
+/// let key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+///
+/// let mut signatures = ServerSignatures::new();
+/// signatures.insert_signature(server_name.clone(), key_id.clone(), "signature".into());
+///
+/// assert_eq!(signatures.get_signature(&server_name, &key_id), Some("signature"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde1",
+    derive(serde1::Deserialize, serde1::Serialize),
+    serde(
+        transparent,
+        crate = "serde1",
+        bound(
+            deserialize = "E: Ord + serde1::Deserialize<'de>, K: Ord + serde1::Deserialize<'de>"
+        )
+    )
+)]
+pub struct Signatures<E, K>(BTreeMap<E, EntitySignatures<K>>);
+
+impl<E: Ord, K: Ord> Signatures<E, K> {
+    /// Creates an empty signature map.
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Adds a signature from the given entity with the given key identifier.
+    ///
+    /// If the entity already has a signature with the same key identifier, it is replaced and the
+    /// previous value is returned.
+    pub fn insert_signature(
+        &mut self,
+        entity: E,
+        key_identifier: K,
+        value: String,
+    ) -> Option<String> {
+        self.0.entry(entity).or_default().insert(key_identifier, value)
+    }
+
+    /// Returns the signature from the given entity with the given key identifier, if any.
+    pub fn get_signature(&self, entity: &E, key_identifier: &K) -> Option<&str> {
+        self.0.get(entity)?.get(key_identifier).map(String::as_str)
+    }
+
+    /// Returns whether the map contains an entry for the given entity.
+    ///
+    /// This doesn't check whether that entry actually holds any signatures, which is only
+    /// possible if it was inserted manually through `DerefMut`.
+    pub fn is_signed_by(&self, entity: &E) -> bool {
+        self.0.contains_key(entity)
+    }
+
+    /// Returns the total number of signatures across all entities.
+    pub fn signature_count(&self) -> usize {
+        self.0.values().map(BTreeMap::len).sum()
+    }
+
+    /// Adds all signatures from `other` to this map.
+    ///
+    /// Signatures that only exist in one of the maps are kept. If both maps contain a signature
+    /// from the same entity with the same key identifier, the one from `other` wins.
+    pub fn merge(&mut self, other: Self) {
+        for (entity, signatures) in other.0 {
+            self.0.entry(entity).or_default().extend(signatures);
+        }
+    }
+
+    /// Removes all signatures from the given entity and returns them, if there were any.
+    pub fn remove_entity(&mut self, entity: &E) -> Option<EntitySignatures<K>> {
+        self.0.remove(entity)
+    }
+
+    /// Retains only the signatures for which `f(entity, key_identifier, signature)` returns
+    /// `true`.
+    ///
+    /// Entities that don't have any signatures left afterwards are removed as well.
+    pub fn retain(&mut self, mut f: impl FnMut(&E, &K, &str) -> bool) {
+        self.0 = mem::take(&mut self.0)
+            .into_iter()
+            .filter_map(|(entity, signatures)| {
+                let signatures: EntitySignatures<K> = signatures
+                    .into_iter()
+                    .filter(|(key_identifier, value)| f(&entity, key_identifier, value))
+                    .collect();
+
+                if signatures.is_empty() {
+                    None
+                } else {
+                    Some((entity, signatures))
+                }
+            })
+            .collect();
+    }
+
+    /// Returns an iterator over the entities that have signatures, in ascending order.
+    pub fn entities(&self) -> impl Iterator<Item = &E> {
+        self.0.keys()
+    }
+
+    /// Returns an iterator over the key identifiers of the given entity's signatures, in ascending
+    /// order.
+    ///
+    /// The iterator is empty if there are no signatures from the entity.
+    pub fn key_ids_for(&self, entity: &E) -> impl Iterator<Item = &K> {
+        self.0.get(entity).into_iter().flat_map(|signatures| signatures.keys())
+    }
+
+    /// Returns an iterator over all `(entity, key_identifier, signature)` triples.
+    ///
+    /// Entities and the key identifiers of each entity are visited in ascending order.
+    pub fn iter_all(&self) -> impl Iterator<Item = (&E, &K, &str)> {
+        self.0.iter().flat_map(|(entity, signatures)| {
+            signatures
+                .iter()
+                .map(move |(key_identifier, value)| (entity, key_identifier, value.as_str()))
+        })
+    }
+
+    /// Consumes the `Signatures` and returns the underlying map.
+    pub fn into_inner(self) -> BTreeMap<E, EntitySignatures<K>> {
+        self.0
+    }
+}
+
+impl<E, K> Default for Signatures<E, K> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+impl<E, K> Deref for Signatures<E, K> {
+    type Target = BTreeMap<E, EntitySignatures<K>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, K> DerefMut for Signatures<E, K> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Collects `(entity, key_identifier, signature)` triples into a signature map.
+///
+/// If the same entity and key identifier occur more than once, the last signature wins.
+impl<E: Ord, K: Ord> FromIterator<(E, K, String)> for Signatures<E, K> {
+    fn from_iter<I: IntoIterator<Item = (E, K, String)>>(iter: I) -> Self {
+        let mut signatures = Self::new();
+        signatures.extend(iter);
+        signatures
+    }
+}
+
+/// Adds `(entity, key_identifier, signature)` triples to a signature map.
+///
+/// Existing signatures with the same entity and key identifier are replaced, and if the same
+/// entity and key identifier occur more than once in the iterator, the last signature wins.
+impl<E: Ord, K: Ord> Extend<(E, K, String)> for Signatures<E, K> {
+    fn extend<I: IntoIterator<Item = (E, K, String)>>(&mut self, iter: I) {
+        for (entity, key_identifier, value) in iter {
+            self.insert_signature(entity, key_identifier, value);
+        }
+    }
+}
+
+/// Formats the entities and key identifiers of the signature map on a single line, for human
+/// inspection.
+///
+/// Entities are separated by `; ` and followed by a list of their key identifiers, like
+/// `example.com: [ed25519:1]; example.org: [ed25519:1, ed25519:2]`. Both are listed in ascending
+/// order. The signatures themselves are left out.
+impl<E: Display, K: Display> Display for Signatures<E, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, (entity, signatures)) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str("; ")?;
+            }
+
+            write!(f, "{}: [", entity)?;
+            for (j, key_identifier) in signatures.keys().enumerate() {
+                if j != 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", key_identifier)?;
+            }
+            f.write_str("]")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<E, K> From<BTreeMap<E, EntitySignatures<K>>> for Signatures<E, K> {
+    fn from(map: BTreeMap<E, EntitySignatures<K>>) -> Self {
+        Self(map)
+    }
+}
+
+/// Map of server signatures for an event, grouped by server.
+pub type ServerSignatures = Signatures<ServerNameBox, ServerKeyId>;
+
+impl Signatures<ServerNameBox, ServerKeyId> {
+    /// Creates a `ServerSignatures` from a map of unvalidated server names and key IDs.
+    ///
+    /// Entries are validated in ascending order of their server names and key IDs, and the error
+    /// for the first invalid entry is returned.
+    pub fn from_raw(map: BTreeMap<String, BTreeMap<String, String>>) -> Result<Self, Error> {
+        let mut signatures = Self::new();
+
+        for (server_name, server_signatures) in map {
+            let server_name = ServerNameBox::try_from(server_name)?;
+            let server_signatures = server_signatures
+                .into_iter()
+                .map(|(key_id, value)| Ok((ServerKeyId::try_from(key_id)?, value)))
+                .collect::<Result<_, Error>>()?;
+
+            signatures.0.insert(server_name, server_signatures);
+        }
+
+        Ok(signatures)
+    }
+
+    /// Like `from_raw`, but skips invalid entries instead of failing.
+    ///
+    /// `on_invalid` is called with the offending server name or key ID and the validation error
+    /// for every entry that is skipped, e.g. to log it. If a server name is invalid, all of its
+    /// signatures are skipped. Servers that don't have any valid key IDs aren't added to the map.
+    pub fn from_raw_lenient(
+        map: BTreeMap<String, BTreeMap<String, String>>,
+        mut on_invalid: impl FnMut(&str, Error),
+    ) -> Self {
+        let mut signatures = Self::new();
+
+        for (server_name, server_signatures) in map {
+            let server_name = match ServerNameBox::try_from(server_name.as_str()) {
+                Ok(server_name) => server_name,
+                Err(e) => {
+                    on_invalid(&server_name, e);
+                    continue;
+                }
+            };
+
+            let server_signatures: EntitySignatures<_> = server_signatures
+                .into_iter()
+                .filter_map(|(key_id, value)| match ServerKeyId::try_from(key_id.as_str()) {
+                    Ok(key_id) => Some((key_id, value)),
+                    Err(e) => {
+                        on_invalid(&key_id, e);
+                        None
+                    }
+                })
+                .collect();
+
+            if !server_signatures.is_empty() {
+                signatures.0.insert(server_name, server_signatures);
+            }
+        }
+
+        signatures
+    }
+
+    /// Deserializes a `ServerSignatures`, skipping entries with invalid server names or key IDs.
+    ///
+    /// This is useful for signatures received over federation, where a single malformed key ID
+    /// shouldn't prevent the remaining signatures from being verified. See
+    /// [`from_raw_lenient`](#method.from_raw_lenient) for details.
+    #[cfg(feature = "serde1")]
+    pub fn deserialize_lenient<'de, D>(
+        deserializer: D,
+        on_invalid: impl FnMut(&str, Error),
+    ) -> Result<Self, D::Error>
+    where
+        D: serde1::Deserializer<'de>,
+    {
+        use serde1::Deserialize as _;
+
+        let map = BTreeMap::<String, BTreeMap<String, String>>::deserialize(deserializer)?;
+        Ok(Self::from_raw_lenient(map, on_invalid))
+    }
+
+    /// Returns an iterator over the signatures from the given server that use the given algorithm.
+    ///
+    /// The key IDs are compared by their raw algorithm string, so they don't have to be parsed.
+    pub fn keys_for_algorithm(
+        &self,
+        server_name: &ServerName,
+        algorithm: &ServerKeyAlgorithm,
+    ) -> impl Iterator<Item = (&ServerKeyId, &str)> {
+        keys_for_algorithm(self.0.get(server_name), algorithm.as_str())
+    }
+}
+
+impl Signatures<UserId, DeviceKeyId> {
+    /// Returns an iterator over the signatures from the given user that use the given algorithm.
+    ///
+    /// The key IDs are compared by their raw algorithm string, so they don't have to be parsed.
+    pub fn keys_for_algorithm(
+        &self,
+        user_id: &UserId,
+        algorithm: &DeviceKeyAlgorithm,
+    ) -> impl Iterator<Item = (&DeviceKeyId, &str)> {
+        keys_for_algorithm(self.0.get(user_id), algorithm.as_str())
+    }
+}
+
+/// Returns an iterator over the signatures with key IDs starting with `algorithm` and a colon.
+///
+/// Since the key IDs are sorted, these form a contiguous range that starts at `"<algorithm>:"`.
+fn keys_for_algorithm<'a, K>(
+    signatures: Option<&'a EntitySignatures<K>>,
+    algorithm: &str,
+) -> impl Iterator<Item = (&'a K, &'a str)>
+where
+    K: Borrow<str> + Ord,
+{
+    let prefix = format!("{}:", algorithm);
+    let range = signatures.map(|signatures| {
+        signatures.range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+    });
+
+    range
+        .into_iter()
+        .flatten()
+        .take_while(move |(key_id, _)| (*key_id).borrow().starts_with(prefix.as_str()))
+        .map(|(key_id, value)| (key_id, value.as_str()))
+}
+
+#[cfg(feature = "canonical-json")]
+impl Signatures<ServerNameBox, ServerKeyId> {
+    /// Converts the signatures to a JSON object of the form `{ server: { key_id: signature } }`,
+    /// as used in the `signatures` field of signed JSON.
+    ///
+    /// Server names and key IDs are inserted in ascending order, so the keys of the resulting
+    /// objects are sorted the way canonical JSON requires it, regardless of whether `serde_json`
+    /// preserves insertion order.
+    #[cfg_attr(docsrs, doc(cfg(feature = "canonical-json")))]
+    pub fn to_canonical_json_value(&self) -> serde_json::Value {
+        use serde_json::{Map, Value};
+
+        let servers = self
+            .0
+            .iter()
+            .map(|(server_name, signatures)| {
+                let signatures = signatures
+                    .iter()
+                    .map(|(key_id, value)| (key_id.to_string(), Value::String(value.clone())))
+                    .collect::<Map<_, _>>();
+
+                (server_name.to_string(), Value::Object(signatures))
+            })
+            .collect::<Map<_, _>>();
+
+        Value::Object(servers)
+    }
+}
+
+/// Map of device signatures for an event, grouped by user.
+pub type DeviceSignatures = Signatures<UserId, DeviceKeyId>;
+
+/// Adds a signature from the given entity with the given key identifier to `signatures`.
+///
+/// If the entity already has a signature with the same key identifier, it is replaced.
+///
+/// This is equivalent to [`Signatures::insert_signature`](struct.Signatures.html#method.insert_signature).
+///
+/// ```
+/// # use ruma_identifiers::{add_signature, server_name, ServerKeyAlgorithm, ServerKeyId, ServerSignatures};
+/// let mut signatures = ServerSignatures::new();
+/// add_signature(
+///     &mut signatures,
+///     server_name!("example.org"),
+///     ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1"),
+///     "YbJva03ihSj5mPk+CHMJKUKlCXCPFXjXOK6VqBnN9nA2evksQcTq9hpCnBRv8NVkKrK+7Y7Lp2TTSmbQn2fkHg"
+///         .into(),
+/// );
+///
+/// assert_eq!(signatures.len(), 1);
+/// ```
+pub fn add_signature<E, K>(
+    signatures: &mut Signatures<E, K>,
+    entity: E,
+    key_identifier: K,
+    value: String,
+) where
+    E: Ord,
+    K: Ord,
+{
+    signatures.insert_signature(entity, key_identifier, value);
+}
+
+/// (De)serialization helpers for `Signatures` fields, for use with `#[serde(with = "…")]`.
+///
+/// The signatures are (de)serialized as a nested object of the form
+/// `{ entity: { key_identifier: signature } }`, with entities and key identifiers in ascending
+/// order. This matches the `Serialize` and `Deserialize` implementations of `Signatures`, so
+/// structs that (de)serialize signatures can spell out the expected shape in one place.
+///
+/// ```
+/// # use ruma_identifiers::ServerSignatures;
+/// # use serde1::{Deserialize, Serialize};
+/// #[derive(Deserialize, Serialize)]
+/// # #[serde(crate = "serde1")]
+/// struct SignedThing {
+///     #[serde(with = "ruma_identifiers::signatures::serde")]
+///     signatures: ServerSignatures,
+/// }
+/// ```
+#[cfg(feature = "serde1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde {
+    use std::collections::BTreeMap;
+
+    use serde1::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{EntitySignatures, Signatures};
+
+    /// Serializes the signatures as a nested object with sorted keys.
+    pub fn serialize<E, K, S>(
+        signatures: &Signatures<E, K>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Serialize,
+        K: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_map(signatures.0.iter())
+    }
+
+    /// Deserializes the signatures from a nested object.
+    pub fn deserialize<'de, E, K, D>(deserializer: D) -> Result<Signatures<E, K>, D::Error>
+    where
+        E: Ord + Deserialize<'de>,
+        K: Ord + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        BTreeMap::<E, EntitySignatures<K>>::deserialize(deserializer).map(Signatures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, convert::TryFrom};
+
+    #[cfg(feature = "serde")]
+    use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+    use super::add_signature;
+    use crate::{
+        DeviceKeyAlgorithm, DeviceKeyId, DeviceSignatures, Error, ServerKeyAlgorithm, ServerKeyId,
+        ServerName, ServerNameError, ServerSignatures, UserId,
+    };
+
+    #[test]
+    fn add_server_signature() {
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+        let key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+
+        let mut signatures = ServerSignatures::new();
+        add_signature(&mut signatures, server_name.clone(), key_id.clone(), "sig1".into());
+        add_signature(&mut signatures, server_name.clone(), key_id.clone(), "sig2".into());
+
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[&server_name][&key_id], "sig2");
+    }
+
+    #[test]
+    fn entities_and_key_ids() {
+        let example_org = Box::<ServerName>::try_from("example.org").unwrap();
+        let example_com = Box::<ServerName>::try_from("example.com").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(example_org.clone(), key_2.clone(), "sig2".into());
+        signatures.insert_signature(example_org.clone(), key_1.clone(), "sig1".into());
+        signatures.insert_signature(example_com.clone(), key_1.clone(), "sig3".into());
+
+        assert_eq!(signatures.entities().collect::<Vec<_>>(), [&example_com, &example_org]);
+        assert_eq!(signatures.key_ids_for(&example_org).collect::<Vec<_>>(), [&key_1, &key_2]);
+        assert_eq!(signatures.key_ids_for(&example_com).collect::<Vec<_>>(), [&key_1]);
+
+        let example_net = Box::<ServerName>::try_from("example.net").unwrap();
+        assert_eq!(signatures.key_ids_for(&example_net).next(), None);
+    }
+
+    #[test]
+    fn display_server_signatures() {
+        let example_org = Box::<ServerName>::try_from("example.org").unwrap();
+        let example_com = Box::<ServerName>::try_from("example.com").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        assert_eq!(signatures.to_string(), "");
+
+        signatures.insert_signature(example_org.clone(), key_2, "sig2".into());
+        signatures.insert_signature(example_org, key_1.clone(), "sig1".into());
+        signatures.insert_signature(example_com, key_1, "sig3".into());
+
+        assert_eq!(
+            signatures.to_string(),
+            "example.com: [ed25519:1]; example.org: [ed25519:1, ed25519:2]"
+        );
+    }
+
+    #[test]
+    fn get_signature() {
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+        let other_server_name = Box::<ServerName>::try_from("example.com").unwrap();
+        let key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let other_key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        assert_eq!(
+            signatures.insert_signature(server_name.clone(), key_id.clone(), "sig".into()),
+            None
+        );
+
+        assert_eq!(signatures.get_signature(&server_name, &key_id), Some("sig"));
+        assert_eq!(signatures.get_signature(&server_name, &other_key_id), None);
+        assert_eq!(signatures.get_signature(&other_server_name, &key_id), None);
+    }
+
+    #[test]
+    fn is_signed_by() {
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+        let other_server_name = Box::<ServerName>::try_from("example.com").unwrap();
+        let key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+
+        let mut signatures = ServerSignatures::new();
+        assert!(!signatures.is_signed_by(&server_name));
+
+        signatures.insert_signature(server_name.clone(), key_id, "sig".into());
+        assert!(signatures.is_signed_by(&server_name));
+        assert!(!signatures.is_signed_by(&other_server_name));
+    }
+
+    #[test]
+    fn signature_count() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        assert_eq!(signatures.signature_count(), 0);
+
+        signatures.insert_signature(server_a.clone(), key_1.clone(), "a1".into());
+        signatures.insert_signature(server_a, key_2, "a2".into());
+        signatures.insert_signature(server_b, key_1, "b1".into());
+
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures.signature_count(), 3);
+    }
+
+    #[test]
+    fn merge_signatures() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(server_a.clone(), key_1.clone(), "a1".into());
+        signatures.insert_signature(server_a.clone(), key_2.clone(), "a2".into());
+
+        let mut other = ServerSignatures::new();
+        other.insert_signature(server_a.clone(), key_2.clone(), "a2-new".into());
+        other.insert_signature(server_b.clone(), key_1.clone(), "b1".into());
+
+        signatures.merge(other);
+
+        assert_eq!(signatures.get_signature(&server_a, &key_1), Some("a1"));
+        assert_eq!(signatures.get_signature(&server_a, &key_2), Some("a2-new"));
+        assert_eq!(signatures.get_signature(&server_b, &key_1), Some("b1"));
+    }
+
+    #[test]
+    fn remove_entity() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(server_a.clone(), key_1.clone(), "a1".into());
+        signatures.insert_signature(server_b.clone(), key_1.clone(), "b1".into());
+
+        let removed = signatures.remove_entity(&server_a).unwrap();
+        assert_eq!(removed[&key_1], "a1");
+        assert!(!signatures.is_signed_by(&server_a));
+        assert!(signatures.is_signed_by(&server_b));
+        assert_eq!(signatures.remove_entity(&server_a), None);
+    }
+
+    #[test]
+    fn retain_signatures() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let ed25519_key = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let custom_key = ServerKeyId::from_parts("io.ruma.test".into(), "1");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(server_a.clone(), ed25519_key.clone(), "a1".into());
+        signatures.insert_signature(server_a.clone(), custom_key.clone(), "a2".into());
+        signatures.insert_signature(server_b.clone(), custom_key.clone(), "b1".into());
+
+        signatures.retain(|_, key_id, _| key_id.matches_algorithm(&ServerKeyAlgorithm::Ed25519));
+
+        assert_eq!(signatures.signature_count(), 1);
+        assert_eq!(signatures.get_signature(&server_a, &ed25519_key), Some("a1"));
+        assert!(!signatures.is_signed_by(&server_b));
+    }
+
+    #[test]
+    fn iter_all_signatures() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(server_b.clone(), key_1.clone(), "b1".into());
+        signatures.insert_signature(server_a.clone(), key_2.clone(), "a2".into());
+        signatures.insert_signature(server_a.clone(), key_1.clone(), "a1".into());
+
+        assert_eq!(
+            signatures.iter_all().collect::<Vec<_>>(),
+            vec![(&server_a, &key_1, "a1"), (&server_a, &key_2, "a2"), (&server_b, &key_1, "b1"),]
+        );
+    }
+
+    #[test]
+    fn server_signatures_from_raw() {
+        let mut map = BTreeMap::new();
+        map.insert("a.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("a.example.org").unwrap().insert("ed25519:1".to_owned(), "a1".to_owned());
+        map.get_mut("a.example.org").unwrap().insert("ed25519:2".to_owned(), "a2".to_owned());
+        map.insert("b.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("b.example.org").unwrap().insert("ed25519:1".to_owned(), "b1".to_owned());
+
+        let signatures = ServerSignatures::from_raw(map).unwrap();
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        assert_eq!(signatures.iter_all().count(), 3);
+        assert_eq!(signatures.get_signature(&server_a, &key_1), Some("a1"));
+        assert_eq!(signatures.get_signature(&server_a, &key_2), Some("a2"));
+        assert_eq!(signatures.get_signature(&server_b, &key_1), Some("b1"));
+    }
+
+    #[test]
+    fn server_signatures_from_raw_with_invalid_entries() {
+        let mut map = BTreeMap::new();
+        map.insert("a.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("a.example.org").unwrap().insert("ed25519:1".to_owned(), "a1".to_owned());
+        map.insert("b.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("b.example.org").unwrap().insert("ed25519".to_owned(), "b1".to_owned());
+        assert_eq!(
+            ServerSignatures::from_raw(map.clone()).unwrap_err(),
+            Error::MissingServerKeyDelimiter { len: 7 }
+        );
+
+        map.insert("c.example.org.".to_owned(), BTreeMap::new());
+        map.remove("b.example.org");
+        assert_eq!(
+            ServerSignatures::from_raw(map).unwrap_err(),
+            Error::InvalidServerName(ServerNameError::EmptyLabel)
+        );
+    }
+
+    #[test]
+    fn server_signatures_from_raw_lenient() {
+        let mut map = BTreeMap::new();
+        map.insert("a.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("a.example.org").unwrap().insert("ed25519:1".to_owned(), "a1".to_owned());
+        map.get_mut("a.example.org").unwrap().insert("ed25519".to_owned(), "a2".to_owned());
+        map.insert("b.example.org".to_owned(), BTreeMap::new());
+        map.get_mut("b.example.org").unwrap().insert(":1".to_owned(), "b1".to_owned());
+        map.insert("c.example.org.".to_owned(), BTreeMap::new());
+        map.get_mut("c.example.org.").unwrap().insert("ed25519:1".to_owned(), "c1".to_owned());
+
+        let mut invalid = Vec::new();
+        let signatures =
+            ServerSignatures::from_raw_lenient(map, |s, e| invalid.push((s.to_owned(), e)));
+
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let key_id = ServerKeyId::try_from("ed25519:1").unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures.signature_count(), 1);
+        assert_eq!(signatures.get_signature(&server_a, &key_id), Some("a1"));
+        assert_eq!(
+            invalid,
+            [
+                ("ed25519".to_owned(), Error::MissingServerKeyDelimiter { len: 7 }),
+                (":1".to_owned(), Error::EmptyKeyAlgorithm),
+                (
+                    "c.example.org.".to_owned(),
+                    Error::InvalidServerName(ServerNameError::EmptyLabel)
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_server_signatures_lenient() {
+        let json = json!({
+            "example.org": {
+                "ed25519:1": "valid",
+                "ed25519:not-valid": "invalid",
+            }
+        });
+
+        let mut invalid_count = 0;
+        let signatures =
+            ServerSignatures::deserialize_lenient(json.clone(), |_, _| invalid_count += 1).unwrap();
+
+        assert_eq!(invalid_count, 1);
+        assert_eq!(
+            to_json_value(&signatures).unwrap(),
+            json!({ "example.org": { "ed25519:1": "valid" } })
+        );
+        assert!(from_json_value::<ServerSignatures>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn signatures_field_with_serde_helpers() {
+        use serde1::{Deserialize, Serialize};
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(crate = "serde1")]
+        struct Pdu {
+            #[serde(with = "crate::signatures::serde")]
+            signatures: ServerSignatures,
+        }
+
+        let json = json!({
+            "signatures": {
+                "b.example.org": { "ed25519:2": "b2", "ed25519:1": "b1" },
+                "a.example.org": { "ed25519:1": "a1" },
+            }
+        });
+
+        let pdu: Pdu = from_json_value(json.clone()).unwrap();
+        assert_eq!(pdu.signatures.signature_count(), 3);
+        assert_eq!(to_json_value(&pdu).unwrap(), json);
+        assert_eq!(
+            serde_json::to_string(&pdu).unwrap(),
+            concat!(
+                r#"{"signatures":{"a.example.org":{"ed25519:1":"a1"},"#,
+                r#""b.example.org":{"ed25519:1":"b1","ed25519:2":"b2"}}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn keys_for_algorithm() {
+        let user_id = UserId::try_from("@alice:example.org").unwrap();
+        let other_user_id = UserId::try_from("@bob:example.org").unwrap();
+
+        let signatures: DeviceSignatures = vec![
+            (user_id.clone(), "curve25519:DEVICE1", "c1"),
+            (user_id.clone(), "ed25519:DEVICE1", "e1"),
+            (user_id.clone(), "ed25519:DEVICE2", "e2"),
+            (user_id.clone(), "ed25519_custom:DEVICE1", "x1"),
+            (user_id.clone(), "signed_curve25519:DEVICE1", "s1"),
+            (other_user_id.clone(), "ed25519:DEVICE3", "e3"),
+        ]
+        .into_iter()
+        .map(|(user_id, key_id, value)| {
+            (user_id, DeviceKeyId::try_from(key_id).unwrap(), value.to_owned())
+        })
+        .collect();
+
+        let ed25519_keys: Vec<_> = signatures
+            .keys_for_algorithm(&user_id, &DeviceKeyAlgorithm::Ed25519)
+            .map(|(key_id, value)| (key_id.as_str(), value))
+            .collect();
+        assert_eq!(ed25519_keys, vec![("ed25519:DEVICE1", "e1"), ("ed25519:DEVICE2", "e2")]);
+
+        let curve25519_keys: Vec<_> = signatures
+            .keys_for_algorithm(&user_id, &DeviceKeyAlgorithm::Curve25519)
+            .map(|(key_id, value)| (key_id.as_str(), value))
+            .collect();
+        assert_eq!(curve25519_keys, vec![("curve25519:DEVICE1", "c1")]);
+
+        let unknown_user_id = UserId::try_from("@carol:example.org").unwrap();
+        assert_eq!(
+            signatures.keys_for_algorithm(&unknown_user_id, &DeviceKeyAlgorithm::Ed25519).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn server_keys_for_algorithm() {
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(
+            server_name.clone(),
+            ServerKeyId::try_from("ed25519:1").unwrap(),
+            "sig1".into(),
+        );
+        signatures.insert_signature(
+            server_name.clone(),
+            ServerKeyId::try_from("io.ruma.test:1").unwrap(),
+            "sig2".into(),
+        );
+
+        let keys: Vec<_> = signatures
+            .keys_for_algorithm(&server_name, &ServerKeyAlgorithm::Ed25519)
+            .map(|(key_id, value)| (key_id.as_str(), value))
+            .collect();
+        assert_eq!(keys, vec![("ed25519:1", "sig1")]);
+    }
+
+    #[test]
+    fn collect_signatures() {
+        let server_a = Box::<ServerName>::try_from("a.example.org").unwrap();
+        let server_b = Box::<ServerName>::try_from("b.example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+
+        let signatures: ServerSignatures = vec![
+            (server_a.clone(), key_1.clone(), "a1".to_owned()),
+            (server_b.clone(), key_1.clone(), "b1".to_owned()),
+            (server_a.clone(), key_1.clone(), "a1-new".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[&server_a].len(), 1);
+        assert_eq!(signatures.get_signature(&server_a, &key_1), Some("a1-new"));
+        assert_eq!(signatures.get_signature(&server_b, &key_1), Some("b1"));
+    }
+
+    #[test]
+    fn extend_signatures() {
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+        let key_1 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        let key_2 = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "2");
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(server_name.clone(), key_1.clone(), "sig1".into());
+        signatures.extend(vec![
+            (server_name.clone(), key_1.clone(), "sig1-new".to_owned()),
+            (server_name.clone(), key_2.clone(), "sig2".to_owned()),
+        ]);
+
+        assert_eq!(signatures.get_signature(&server_name, &key_1), Some("sig1-new"));
+        assert_eq!(signatures.get_signature(&server_name, &key_2), Some("sig2"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn signatures_serde() {
+        let json = json!({ "example.org": { "ed25519:1": "sig" } });
+
+        let signatures: ServerSignatures = from_json_value(json.clone()).unwrap();
+        let server_name = Box::<ServerName>::try_from("example.org").unwrap();
+        let key_id = ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1");
+        assert_eq!(signatures.get_signature(&server_name, &key_id), Some("sig"));
+
+        assert_eq!(to_json_value(&signatures).unwrap(), json);
+    }
+
+    #[cfg(feature = "canonical-json")]
+    #[test]
+    fn server_signatures_to_canonical_json() {
+        // Signature of `{}` from the documentation of `ruma_signatures::sign_json`.
+        let signature =
+            "K8280/U9SSy9IVtjBuVeLr+HpOB4BQFWbg+UZaADMtTdGYI7Geitb76LTrr5QV/7Xg4ahLwYGYZzuHGZKM5ZAQ";
+
+        let mut signatures = ServerSignatures::new();
+        signatures.insert_signature(
+            Box::<ServerName>::try_from("domain").unwrap(),
+            ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "1"),
+            signature.into(),
+        );
+        signatures.insert_signature(
+            Box::<ServerName>::try_from("another.domain").unwrap(),
+            ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "b"),
+            "sig_b".into(),
+        );
+        signatures.insert_signature(
+            Box::<ServerName>::try_from("another.domain").unwrap(),
+            ServerKeyId::from_parts(ServerKeyAlgorithm::Ed25519, "a"),
+            "sig_a".into(),
+        );
+
+        let value = signatures.to_canonical_json_value();
+        assert_eq!(
+            value,
+            json!({
+                "another.domain": { "ed25519:a": "sig_a", "ed25519:b": "sig_b" },
+                "domain": { "ed25519:1": signature },
+            })
+        );
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            format!(
+                r#"{{"another.domain":{{"ed25519:a":"sig_a","ed25519:b":"sig_b"}},"domain":{{"ed25519:1":"{}"}}}}"#,
+                signature
+            )
+        );
+    }
+
+    #[test]
+    fn add_device_signature() {
+        let user_id = UserId::try_from("@alice:example.org").unwrap();
+        let ed25519_key_id = DeviceKeyId::from_parts(DeviceKeyAlgorithm::Ed25519, "DEVID".into());
+        let curve25519_key_id =
+            DeviceKeyId::from_parts(DeviceKeyAlgorithm::Curve25519, "DEVID".into());
+
+        let mut signatures = DeviceSignatures::new();
+        add_signature(&mut signatures, user_id.clone(), ed25519_key_id.clone(), "sig1".into());
+        add_signature(&mut signatures, user_id.clone(), curve25519_key_id.clone(), "sig2".into());
+
+        let user_signatures = &signatures[&user_id];
+        assert_eq!(user_signatures.len(), 2);
+        assert_eq!(user_signatures[&ed25519_key_id], "sig1");
+        assert_eq!(user_signatures[&curve25519_key_id], "sig2");
+    }
+}
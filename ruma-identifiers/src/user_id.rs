@@ -95,9 +95,9 @@ fn try_from<S>(user_id: S) -> Result<UserId, Error>
 where
     S: AsRef<str> + Into<Box<str>>,
 {
-    let (colon_idx, is_historical) =
+    let (colon_idx, is_fully_conforming) =
         ruma_identifiers_validation::user_id::validate(user_id.as_ref())?;
-    Ok(UserId { full_id: user_id.into(), colon_idx, is_historical: !is_historical })
+    Ok(UserId { full_id: user_id.into(), colon_idx, is_historical: !is_fully_conforming })
 }
 
 common_impls!(UserId, try_from, "a Matrix user ID");
@@ -112,7 +112,7 @@ mod tests {
     use serde_json::{from_str, to_string};
 
     use super::UserId;
-    use crate::{Error, ServerName};
+    use crate::{Error, ServerName, ServerNameError};
 
     #[test]
     fn valid_user_id_from_str() {
@@ -176,6 +176,13 @@ mod tests {
         assert!(user_id.is_historical());
     }
 
+    #[test]
+    fn truncated_user_id() {
+        let user_id = UserId::try_from("@carl:example.com").expect("Failed to create UserId.");
+        assert_eq!(user_id.truncated(32), "@carl:example.com");
+        assert_eq!(user_id.truncated(8), "@carl:ex…");
+    }
+
     #[test]
     fn uppercase_user_id() {
         let user_id = UserId::try_from("@CARL:example.com").expect("Failed to create UserId.");
@@ -231,6 +238,23 @@ mod tests {
         assert!(!user_id.is_historical());
     }
 
+    #[test]
+    fn user_id_parts_with_port() {
+        let user_id = UserId::try_from("@carl:example.com:5000").expect("Failed to create UserId.");
+        assert_eq!(user_id.localpart(), "carl");
+        assert_eq!(user_id.server_name(), "example.com:5000");
+        assert_eq!(user_id.server_name().port(), Some(5000));
+    }
+
+    #[test]
+    fn historical_user_id_parts_with_port() {
+        let user_id =
+            UserId::try_from("@Carl/Test:example.com:5000").expect("Failed to create UserId.");
+        assert_eq!(user_id.localpart(), "Carl/Test");
+        assert_eq!(user_id.server_name(), "example.com:5000");
+        assert!(user_id.is_historical());
+    }
+
     #[test]
     fn invalid_characters_in_user_id_localpart() {
         assert_eq!(UserId::try_from("@te\nst:example.com").unwrap_err(), Error::InvalidCharacters);
@@ -248,14 +272,17 @@ mod tests {
 
     #[test]
     fn invalid_user_id_host() {
-        assert_eq!(UserId::try_from("@carl:/").unwrap_err(), Error::InvalidServerName);
+        assert_eq!(
+            UserId::try_from("@carl:/").unwrap_err(),
+            Error::InvalidServerName(ServerNameError::InvalidCharacters)
+        );
     }
 
     #[test]
     fn invalid_user_id_port() {
         assert_eq!(
             UserId::try_from("@carl:example.com:notaport").unwrap_err(),
-            Error::InvalidServerName
+            Error::InvalidServerName(ServerNameError::InvalidPort)
         );
     }
 }
@@ -0,0 +1,47 @@
+//! Checks that validating key IDs with `validate_only` doesn't allocate.
+//!
+//! This is a separate test binary because it replaces the global allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ruma_identifiers::{DeviceKeyId, Error, ServerKeyId};
+
+/// Wraps the system allocator to count the number of allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn validate_only_does_not_allocate() {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let results = [
+        DeviceKeyId::validate_only("ed25519:JLAFKJWSCS"),
+        DeviceKeyId::validate_only(":JLAFKJWSCS"),
+        ServerKeyId::validate_only("ed25519:Abc_1"),
+        ServerKeyId::validate_only("ed25519:"),
+    ];
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    assert_eq!(
+        results,
+        [Ok(()), Err(Error::EmptyKeyAlgorithm), Ok(()), Err(Error::InvalidKeyVersion)]
+    );
+    assert_eq!(allocations, 0);
+}